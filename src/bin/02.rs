@@ -1,111 +1,136 @@
-use std::cmp::Ordering;
+advent_of_code::solution!(2, report = report);
 
-advent_of_code::solution!(2);
+use std::fmt::Display;
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let reports = input.split('\n');
-    Some(
-        reports
-            .map(is_safe)
-            .filter(|x| *x)
-            .count()
-            .try_into()
-            .unwrap(),
-    )
-}
+use advent_of_code::template::options;
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let reports = input.split('\n');
-    Some(
-        reports
-            .map(is_any_permutation_safe)
-            .filter(|x| *x)
-            .count()
-            .try_into()
-            .unwrap(),
-    )
+/// Why a report is unsafe, and how the problem dampener repairs it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    /// Index of the report in the input.
+    pub report: usize,
+    /// Index of the first level that breaks the rules.
+    pub level: usize,
+    pub value: u32,
+    /// Index of the level whose removal makes the report safe, if any.
+    pub removal: Option<usize>,
 }
 
-pub fn is_any_permutation_safe(input: &str) -> bool {
-    if input.is_empty() {
-        return false;
-    }
-    if is_safe(input) {
-        return true;
-    }
-    let v_reports: Vec<_> = input
-        .split_whitespace()
-        .map(|x| x.parse::<u32>().unwrap())
-        .collect();
-    for skip_index in 0..v_reports.len() {
-        if is_safe(
-            &v_reports
-                .iter()
-                .enumerate()
-                .filter(|&(i, _)| i != skip_index)
-                .map(|(_, v)| v.to_string())
-                .collect::<Vec<_>>()
-                .join(" "),
-        ) {
-            return true;
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "report {}: level {} ({}) is unsafe, ",
+            self.report + 1,
+            self.level + 1,
+            self.value
+        )?;
+        match self.removal {
+            Some(removal) => write!(f, "removing level {} repairs it", removal + 1),
+            None => write!(f, "no single removal repairs it"),
         }
     }
-    false
 }
 
-pub fn is_safe(reports: &str) -> bool {
-    if reports.is_empty() {
-        return false;
-    }
-    let mut safe = true;
-    let mut v_reports = reports
-        .split_whitespace()
-        .map(|x| x.parse::<u32>().unwrap());
-
-    let mut increasing = false;
-    let mut last_val = v_reports
-        .next()
-        .expect("THere should be at least one value");
-    let mut first_check = true;
-
-    for val in v_reports {
-        if first_check {
-            match val.cmp(&last_val) {
-                Ordering::Greater => {
-                    increasing = true;
-                    first_check = true;
-                }
-                Ordering::Less => {
-                    increasing = false;
-                    first_check = false;
-                }
-                Ordering::Equal => {}
-            }
-        }
-        if !level_is_valid(last_val, val, increasing) {
-            safe = false;
-            break;
-        }
+pub fn parse_reports(input: &str) -> Vec<Vec<u32>> {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.split_whitespace()
+                .map(|x| x.parse::<u32>().expect("Levels should be numbers"))
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the index of the first level that differs from the previous one by less than 1 or
+/// more than 3, or that changes direction. The direction is set by the first two levels.
+pub fn first_unsafe_level(levels: impl IntoIterator<Item = u32>) -> Option<usize> {
+    let mut levels = levels.into_iter();
+    let mut previous = levels.next()?;
+    let mut increasing = None;
 
-        last_val = val;
+    for (idx, level) in levels.enumerate() {
+        let is_increasing = *increasing.get_or_insert(level > previous);
+        if is_increasing != (level > previous) || !(1..=3).contains(&level.abs_diff(previous)) {
+            return Some(idx + 1);
+        }
+        previous = level;
     }
-    safe
+
+    None
 }
 
-pub fn level_is_valid(last_level: u32, current_level: u32, increasing: bool) -> bool {
-    if increasing && last_level > current_level {
-        return false;
-    }
-    if !increasing && last_level < current_level {
-        return false;
+pub fn is_safe(levels: impl IntoIterator<Item = u32>) -> bool {
+    first_unsafe_level(levels).is_none()
+}
+
+/// Iterates over the levels of a report with the level at `skip` removed.
+pub fn without(levels: &[u32], skip: usize) -> impl Iterator<Item = u32> + '_ {
+    levels
+        .iter()
+        .enumerate()
+        .filter(move |&(idx, _)| idx != skip)
+        .map(|(_, level)| *level)
+}
+
+/// Finds a level whose removal makes an unsafe report safe.
+///
+/// Only the levels just before the first unsafe level `i` can help: the levels before `i` are
+/// safe among themselves, so removing one further back leaves both the direction and the broken
+/// pair in place. `i - 2` is included because removing it may flip the direction.
+pub fn find_removal(levels: &[u32], unsafe_level: usize) -> Option<usize> {
+    (unsafe_level.saturating_sub(2)..=unsafe_level).find(|&skip| is_safe(without(levels, skip)))
+}
+
+pub fn diagnose(reports: &[Vec<u32>]) -> Vec<Diagnosis> {
+    reports
+        .iter()
+        .enumerate()
+        .filter_map(|(report, levels)| {
+            let level = first_unsafe_level(levels.iter().copied())?;
+            Some(Diagnosis {
+                report,
+                level,
+                value: levels[level],
+                removal: find_removal(levels, level),
+            })
+        })
+        .collect()
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let reports = parse_reports(input);
+    let safe = reports
+        .iter()
+        .filter(|levels| is_safe(levels.iter().copied()))
+        .count();
+    Some(safe as u32)
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    let reports = parse_reports(input);
+    let unrepairable = diagnose(&reports)
+        .iter()
+        .filter(|d| d.removal.is_none())
+        .count();
+    Some((reports.len() - unrepairable) as u32)
+}
+
+/// Explains the unsafe reports with `--option diagnose`.
+pub fn report(input: &str) {
+    if options::is_set("diagnose") {
+        for diagnosis in diagnose(&parse_reports(input)) {
+            eprintln!("{diagnosis}");
+        }
     }
-    let delta = (current_level as i32 - last_level as i32).abs();
-    (1..=3).contains(&delta)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::Differential;
 
     #[test]
     fn test_part_one() {
@@ -118,4 +143,40 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(4));
     }
+
+    #[test]
+    fn test_diagnose() {
+        let reports = parse_reports(&advent_of_code::template::read_file("examples", DAY));
+        let diagnoses = diagnose(&reports);
+        assert_eq!(diagnoses.len(), 4);
+        assert_eq!(
+            diagnoses[0],
+            Diagnosis {
+                report: 1,
+                level: 2,
+                value: 7,
+                removal: None,
+            }
+        );
+        assert_eq!(
+            diagnoses[2].to_string(),
+            "report 4: level 3 (2) is unsafe, removing level 2 repairs it"
+        );
+    }
+
+    #[test]
+    fn test_removal_matches_brute_force() {
+        Differential::new(|rng| rng.vec(2..=8, |rng| rng.range(1..=12) as u32))
+            .cases(1000)
+            .check(
+                |levels| {
+                    is_safe(levels.iter().copied())
+                        || (0..levels.len()).any(|skip| is_safe(without(levels, skip)))
+                },
+                |levels| match first_unsafe_level(levels.iter().copied()) {
+                    Some(level) => find_removal(levels, level).is_some(),
+                    None => true,
+                },
+            );
+    }
 }
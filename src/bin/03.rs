@@ -1,40 +1,205 @@
-use regex::Regex;
 advent_of_code::solution!(3);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    make_mult(input)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(u32, u32),
+    Do,
+    Dont,
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let dont_re = Regex::new(r"(mul\((?<op1>\d{1,3}),(?<op2>\d{1,3})\))|(?<dt>don't\(\))|(?<d>do\(\))").unwrap();
-    let matches = dont_re.captures_iter(input);
-    let mut enabled = true;
-    let mut sum = 0;
-    for mat in matches {
-        if mat.name("dt").is_some() {
-            enabled = false;
-        } else if mat.name("d").is_some() {
-            enabled = true;
-        }
-
-        if enabled {
-            if let (Some(op1), Some(op2)) = (mat.name("op1"), mat.name("op2")) {
-                sum += op1.as_str().parse::<u32>().unwrap() * op2.as_str().parse::<u32>().unwrap();
+/// Scans corrupted memory for `mul(X,Y)`, `do()` and `don't()`, where `X` and `Y` have one to
+/// three digits. Everything else is skipped, one byte at a time, like a regex search would.
+pub struct Tokenizer<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Tokenizer {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    /// Consumes `token` if the input continues with it.
+    fn eat(&mut self, token: &[u8]) -> bool {
+        if self.input[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            return true;
+        }
+        false
+    }
+
+    /// Consumes a number of one to three digits.
+    fn number(&mut self) -> Option<u32> {
+        let digits = self.input[self.pos..]
+            .iter()
+            .take(4)
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if !(1..=3).contains(&digits) {
+            return None;
+        }
+
+        let number = self.input[self.pos..self.pos + digits]
+            .iter()
+            .fold(0, |number, c| number * 10 + u32::from(c - b'0'));
+        self.pos += digits;
+        Some(number)
+    }
+
+    /// Reads the instruction at the current position. The position is left anywhere inside a
+    /// corrupted instruction.
+    fn instruction(&mut self) -> Option<Instruction> {
+        if self.eat(b"mul(") {
+            let lhs = self.number()?;
+            self.eat(b",").then_some(())?;
+            let rhs = self.number()?;
+            return self.eat(b")").then_some(Instruction::Mul(lhs, rhs));
+        }
+        if self.eat(b"do()") {
+            return Some(Instruction::Do);
+        }
+        if self.eat(b"don't()") {
+            return Some(Instruction::Dont);
+        }
+        None
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = Instruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(skip) = self.input[self.pos..]
+            .iter()
+            .position(|c| matches!(c, b'm' | b'd'))
+        {
+            let start = self.pos + skip;
+            self.pos = start;
+            if let Some(instruction) = self.instruction() {
+                return Some(instruction);
             }
+            self.pos = start + 1;
         }
+
+        self.pos = self.input.len();
+        None
     }
-    Some(sum)
 }
 
-pub fn make_mult(input: &str) -> Option<u32> {
-    let re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
-    let matches = re.captures_iter(input);
-    Some(matches.map(|x| x[1].parse::<u32>().unwrap() * x[2].parse::<u32>().unwrap()).sum())
+/// The instructions a machine understands. Instructions it does not know are ignored.
+pub trait InstructionSet {
+    fn execute(&mut self, instruction: Instruction);
+
+    fn result(&self) -> u32;
+}
+
+/// Sums the products of all `mul` instructions.
+#[derive(Debug, Default)]
+pub struct Multiplications {
+    sum: u32,
+}
+
+impl InstructionSet for Multiplications {
+    fn execute(&mut self, instruction: Instruction) {
+        if let Instruction::Mul(lhs, rhs) = instruction {
+            self.sum += lhs * rhs;
+        }
+    }
+
+    fn result(&self) -> u32 {
+        self.sum
+    }
+}
+
+/// Sums the products of the `mul` instructions enabled by the most recent `do()` or `don't()`.
+/// Instructions are enabled at the start.
+#[derive(Debug)]
+pub struct Conditionals {
+    enabled: bool,
+    sum: u32,
+}
+
+impl Default for Conditionals {
+    fn default() -> Self {
+        Conditionals {
+            enabled: true,
+            sum: 0,
+        }
+    }
+}
+
+impl InstructionSet for Conditionals {
+    fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Mul(lhs, rhs) if self.enabled => self.sum += lhs * rhs,
+            Instruction::Mul(..) => {}
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = false,
+        }
+    }
+
+    fn result(&self) -> u32 {
+        self.sum
+    }
+}
+
+pub fn evaluate<S: InstructionSet>(input: &str, mut machine: S) -> u32 {
+    for instruction in Tokenizer::new(input) {
+        machine.execute(instruction);
+    }
+    machine.result()
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    Some(evaluate(input, Multiplications::default()))
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    Some(evaluate(input, Conditionals::default()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::Differential;
+    use regex::Regex;
+    use std::sync::LazyLock;
+
+    /// The regex the solution used before the tokenizer, kept as a reference.
+    static INSTRUCTION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|don't\(\)|do\(\)").unwrap());
+
+    fn tokenize_with_regex(input: &str) -> Vec<Instruction> {
+        INSTRUCTION
+            .captures_iter(input)
+            .map(|captures| match &captures[0] {
+                "do()" => Instruction::Do,
+                "don't()" => Instruction::Dont,
+                _ => Instruction::Mul(captures[1].parse().unwrap(), captures[2].parse().unwrap()),
+            })
+            .collect()
+    }
+
+    /// Renders generated pieces of memory, valid and almost valid instructions with noise.
+    fn memory(pieces: &[(usize, u32, u32)]) -> String {
+        pieces
+            .iter()
+            .map(|&(kind, a, b)| match kind % 10 {
+                0 | 1 => format!("mul({a},{b})"),
+                2 => format!("mul({a},{b}"),
+                3 => format!("mul({a}{b})"),
+                4 => format!("mul ({a},{b})"),
+                5 => "do()".into(),
+                6 => "don't()".into(),
+                7 => "don't(".into(),
+                8 => "m".into(),
+                _ => format!("x{a}"),
+            })
+            .collect()
+    }
 
     #[test]
     fn test_part_one() {
@@ -47,4 +212,38 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(48));
     }
+
+    #[test]
+    fn test_tokenizer() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let instructions: Vec<_> = Tokenizer::new(&input).collect();
+        assert_eq!(
+            instructions,
+            [
+                Instruction::Mul(2, 4),
+                Instruction::Dont,
+                Instruction::Mul(5, 5),
+                Instruction::Mul(11, 8),
+                Instruction::Do,
+                Instruction::Mul(8, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_matches_regex() {
+        Differential::new(|rng| {
+            rng.vec(0..=12, |rng| {
+                // up to four digits, so that some numbers are too long.
+                let a = rng.range(0..=1200) as u32;
+                let b = rng.range(0..=1200) as u32;
+                (rng.index(10), a, b)
+            })
+        })
+        .cases(1000)
+        .check(
+            |pieces| tokenize_with_regex(&memory(pieces)),
+            |pieces| Tokenizer::new(&memory(pieces)).collect::<Vec<_>>(),
+        );
+    }
 }
@@ -1,11 +1,14 @@
-use std::ops::Index;
+advent_of_code::solution!(5, report = report);
 
-advent_of_code::solution!(5);
+use advent_of_code::graph::{CycleError, DiGraph};
 
-pub fn parse_data(input: &str) -> (Vec<Vec<u32>>, Vec<(u32, u32)>) {
+/// Page numbers have two digits, so they are used as nodes of the rule graph directly.
+const MAX_PAGE: usize = 99;
+
+pub fn parse_data(input: &str) -> (Vec<Vec<usize>>, DiGraph) {
     let mut parsing_updates = false;
-    let mut rules: Vec<(u32, u32)> = vec![];
-    let mut updates: Vec<Vec<u32>> = vec![];
+    let mut rules = DiGraph::new(MAX_PAGE + 1);
+    let mut updates: Vec<Vec<usize>> = vec![];
     for line in input.lines() {
         if line.is_empty() {
             parsing_updates = true;
@@ -13,70 +16,54 @@ pub fn parse_data(input: &str) -> (Vec<Vec<u32>>, Vec<(u32, u32)>) {
         }
 
         if !parsing_updates {
-            let t: Vec<_> = line.split('|').collect();
-            let f = t[0].parse::<u32>().unwrap();
-            let s = t[1].parse::<u32>().unwrap();
-            rules.push((f, s));
+            let (f, s) = line.split_once('|').expect("Rules should be `X|Y`");
+            rules.add_edge(f.parse().unwrap(), s.parse().unwrap());
         } else {
-            updates.push(line.split(',').map(|x| x.parse::<u32>().unwrap()).collect());
+            updates.push(line.split(',').map(|x| x.parse().unwrap()).collect());
         }
     }
 
     (updates, rules)
 }
 
+fn middle_page(update: &[usize]) -> u32 {
+    update[update.len() / 2] as u32
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
     let (updates, rules) = parse_data(input);
-    let mut accum: u32 = 0;
-
-    'update_loop: for update in updates {
-        for (idx, page) in update.iter().enumerate() {
-            let specific_rules = rules
-                .iter()
-                .filter(|(first, _)| first == page)
-                .cloned()
-                .filter(|(_, second)| update[0..idx].contains(second))
-                .collect::<Vec<(u32, u32)>>();
-            if !specific_rules.is_empty() {
-                continue 'update_loop;
-            }
-        }
-        accum += update[update.len() / 2];
-    }
-    Some(accum)
+    Some(
+        updates
+            .iter()
+            .filter(|update| rules.is_sorted(update))
+            .map(|update| middle_page(update))
+            .sum(),
+    )
 }
 
+/// Sums the middle pages of the updates that are out of order, after ordering them. Returns
+/// `None` if the rules for an update contain a cycle, so that it has no valid order.
 pub fn part_two(input: &str) -> Option<u32> {
     let (updates, rules) = parse_data(input);
-    let mut accum: u32 = 0;
-    for mut update in updates {
-        let mut curr_page_idx: usize = 0;
-        let mut fixed = false;
-        while curr_page_idx < update.len() {
-            let specific_rules = rules
-                .iter()
-                .filter(|(first, _)| *first == update[curr_page_idx])
-                .cloned()
-                .filter(|(_, second)| update[0..curr_page_idx].contains(second))
-                .collect::<Vec<(u32, u32)>>();
-            // We swap the two elements that failed
-            if !specific_rules.is_empty() {
-                fixed = true;
-                let failed_idx = update
-                    .iter()
-                    .position(|x| *x == specific_rules[0].1)
-                    .expect("We know it exists");
-                update.swap(curr_page_idx, failed_idx);
-                curr_page_idx = failed_idx;
-            } else {
-                curr_page_idx += 1;
-            }
-        }
-        if fixed {
-            accum += update[update.len() / 2];
-        }
+    let fixed = order_updates(&updates, &rules).ok()?;
+    Some(fixed.iter().map(|update| middle_page(update)).sum())
+}
+
+/// Orders the updates that are out of order.
+fn order_updates(updates: &[Vec<usize>], rules: &DiGraph) -> Result<Vec<Vec<usize>>, CycleError> {
+    updates
+        .iter()
+        .filter(|update| !rules.is_sorted(update))
+        .map(|update| rules.toposort(update))
+        .collect()
+}
+
+/// Explains why part two has no answer when the rules contain a cycle.
+pub fn report(input: &str) {
+    let (updates, rules) = parse_data(input);
+    if let Err(e) = order_updates(&updates, &rules) {
+        eprintln!("Cannot order the pages: {e}");
     }
-    Some(accum)
 }
 
 #[cfg(test)]
@@ -94,4 +81,14 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(123));
     }
+
+    #[test]
+    fn test_cyclic_rules() {
+        let input = "11|22\n22|33\n33|11\n\n22,11\n11,22,33";
+        let (_, rules) = parse_data(input);
+        assert_eq!(rules.toposort(&[22, 11]), Ok(vec![11, 22]));
+        assert!(rules.toposort(&[11, 22, 33]).is_err());
+        assert_eq!(part_two(input), None);
+        assert_eq!(part_two("11|22\n22|33\n33|11\n\n22,11"), Some(22));
+    }
 }
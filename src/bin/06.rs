@@ -1,28 +1,54 @@
-advent_of_code::solution!(6);
+//! tags: grid, simulation
+advent_of_code::solution!(6, generator = generate);
 
+use advent_of_code::template::differential::Rng;
+use advent_of_code::template::visualize::{self, Frame};
 use rayon::prelude::*;
-use std::collections::HashSet;
 
 #[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
 enum Direction {
     North,
-    South,
     East,
+    South,
     West,
 }
 
-#[derive(Debug, Hash, PartialEq, Clone)]
-pub struct Puzzle {
-    map: Vec<Vec<char>>,
-    guard_direction: Direction,
-    guard_position: Option<(usize, usize)>,
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+}
+
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Guard {
+    position: (usize, usize),
+    direction: Direction,
+}
+
+/// Marks a jump that leaves the lab.
+const EXIT: usize = usize::MAX;
+
+pub struct Lab {
+    width: usize,
+    height: usize,
+    obstacles: Vec<bool>,
+    /// For every cell and direction, the index of the cell in front of the next obstacle, or
+    /// [`EXIT`] if the guard leaves the lab.
+    jumps: Vec<[usize; 4]>,
 }
 
-impl std::fmt::Display for Puzzle {
+impl std::fmt::Display for Lab {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.map {
-            for &ch in row {
-                write!(f, "{}", ch)?;
+        for row in self.obstacles.chunks(self.width) {
+            for &obstacle in row {
+                write!(f, "{}", if obstacle { '#' } else { '.' })?;
             }
             writeln!(f)?; // newline after each row
         }
@@ -30,156 +56,276 @@ impl std::fmt::Display for Puzzle {
     }
 }
 
-pub fn parse_input(input: &str) -> Puzzle {
-    let mut map: Vec<Vec<char>> = vec![];
-    let mut guard_position: Option<(usize, usize)> = None;
-    let guard_direction = Direction::North;
-    for (line_idx, line) in input.lines().enumerate() {
-        let line = line.chars().collect::<Vec<char>>();
-        if let Some(starting_col) = line.iter().position(|x| *x == '^') {
-            guard_position = Some((starting_col, line_idx));
+impl Lab {
+    pub fn new(width: usize, height: usize, obstacles: Vec<bool>) -> Self {
+        let mut lab = Lab {
+            width,
+            height,
+            obstacles,
+            jumps: vec![[EXIT; 4]; width * height],
+        };
+
+        // the jump of a cell continues the jump of the cell ahead, so that one is filled first.
+        for direction in Direction::ALL {
+            let indices: Box<dyn Iterator<Item = usize>> = match direction {
+                Direction::North | Direction::West => Box::new(0..width * height),
+                Direction::South | Direction::East => Box::new((0..width * height).rev()),
+            };
+            for idx in indices {
+                let jump = match lab.ahead(lab.position(idx), direction) {
+                    None => EXIT,
+                    Some(ahead) if lab.obstacles[lab.index(ahead)] => idx,
+                    Some(ahead) => lab.jumps[lab.index(ahead)][direction as usize],
+                };
+                lab.jumps[idx][direction as usize] = jump;
+            }
         }
-        map.push(line);
+
+        lab
     }
-    Puzzle {
-        map,
-        guard_direction,
-        guard_position,
+
+    fn cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        y * self.width + x
+    }
+
+    fn position(&self, idx: usize) -> (usize, usize) {
+        (idx % self.width, idx / self.width)
     }
-}
 
-pub fn can_escapce(mut puzzle: Puzzle) -> bool {
-    let mut seen_locations: HashSet<(usize, usize, Direction)> = HashSet::new();
+    /// Index of a guard state in a [`BitSet`] of `4 * cells` states.
+    fn state(&self, guard: Guard) -> usize {
+        self.index(guard.position) * 4 + guard.direction as usize
+    }
 
-    while let Some((guard_x, guard_y)) = puzzle.guard_position {
-        if !seen_locations.insert((guard_x, guard_y, puzzle.guard_direction)) {
-            return false;
+    /// The cell in front of `position`, or `None` at the border of the lab.
+    fn ahead(&self, (x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        match direction {
+            Direction::North => y.checked_sub(1).map(|y| (x, y)),
+            Direction::South => (y + 1 < self.height).then_some((x, y + 1)),
+            Direction::West => x.checked_sub(1).map(|x| (x, y)),
+            Direction::East => (x + 1 < self.width).then_some((x + 1, y)),
         }
+    }
 
-        match puzzle.guard_direction {
-            Direction::North => {
-                puzzle.guard_position = guard_y.checked_sub(1).map(|new_y| (guard_x, new_y));
-            }
-            Direction::South => {
-                puzzle.guard_position = guard_y.checked_add(1).and_then(|new_y| {
-                    if new_y >= puzzle.map.len() {
-                        None
-                    } else {
-                        Some((guard_x, new_y))
-                    }
-                });
+    fn is_blocked(&self, position: (usize, usize), extra: Option<(usize, usize)>) -> bool {
+        self.obstacles[self.index(position)] || extra == Some(position)
+    }
+
+    /// Moves the guard one cell forward, or turns her right in front of an obstacle. Returns
+    /// `None` once she leaves the lab.
+    pub fn step(&self, guard: Guard, extra: Option<(usize, usize)>) -> Option<Guard> {
+        let ahead = self.ahead(guard.position, guard.direction)?;
+        Some(if self.is_blocked(ahead, extra) {
+            Guard {
+                direction: guard.direction.turn_right(),
+                ..guard
             }
-            Direction::West => {
-                puzzle.guard_position = guard_x.checked_sub(1).map(|new_x| (new_x, guard_y))
+        } else {
+            Guard {
+                position: ahead,
+                ..guard
             }
-            Direction::East => {
-                puzzle.guard_position = guard_x.checked_add(1).and_then(|new_x| {
-                    if new_x >= puzzle.map[0].len() {
-                        None
-                    } else {
-                        Some((new_x, guard_y))
+        })
+    }
+
+    /// Moves the guard straight to the next obstacle and turns her right. Returns `None` once
+    /// she leaves the lab.
+    pub fn jump(&self, guard: Guard, extra: Option<(usize, usize)>) -> Option<Guard> {
+        let (x, y) = guard.position;
+        let stop = self.jumps[self.index(guard.position)][guard.direction as usize];
+
+        // the jump table does not know the extra obstacle, it stops the guard if it comes first.
+        let stop = match extra {
+            Some((ex, ey)) => {
+                let (stop_x, stop_y) = if stop == EXIT {
+                    (usize::MAX, usize::MAX)
+                } else {
+                    self.position(stop)
+                };
+                match guard.direction {
+                    Direction::North if ex == x && ey < y && (stop == EXIT || ey >= stop_y) => {
+                        self.index((x, ey + 1))
+                    }
+                    Direction::South if ex == x && ey > y && ey <= stop_y => {
+                        self.index((x, ey - 1))
                     }
-                });
+                    Direction::West if ey == y && ex < x && (stop == EXIT || ex >= stop_x) => {
+                        self.index((ex + 1, y))
+                    }
+                    Direction::East if ey == y && ex > x && ex <= stop_x => self.index((ex - 1, y)),
+                    _ => stop,
+                }
+            }
+            None => stop,
+        };
+
+        (stop != EXIT).then(|| Guard {
+            position: self.position(stop),
+            direction: guard.direction.turn_right(),
+        })
+    }
+
+    /// Walks the guard cell by cell and returns the visited cells. Stops when she leaves the lab
+    /// or walks in a loop.
+    pub fn walk(&self, start: Guard) -> Vec<bool> {
+        let mut visited = vec![false; self.cells()];
+        let mut seen = BitSet::new(self.cells() * 4);
+        let mut guard = Some(start);
+        let mut last = start;
+
+        while let Some(current) = guard.filter(|guard| seen.insert(self.state(*guard))) {
+            visited[self.index(current.position)] = true;
+            guard = self.step(current, None);
+            last = current;
+
+            if guard.is_some_and(|next| next.direction != current.direction) {
+                visualize::record(|| render_path(self, current, &visited));
             }
         }
-        if let Some((new_x, new_y)) = puzzle.guard_position {
-            if puzzle.map[new_y][new_x] == '.' {
-                puzzle.map[new_y][new_x] = '^';
-                puzzle.map[guard_y][guard_x] = '.';
-            } else if puzzle.map[new_y][new_x] == '#' {
-                puzzle.guard_position = Some((guard_x, guard_y));
-                puzzle.guard_direction = match puzzle.guard_direction {
-                    Direction::North => Direction::East,
-                    Direction::East => Direction::South,
-                    Direction::South => Direction::West,
-                    Direction::West => Direction::North,
-                };
+
+        visualize::record(|| render_path(self, last, &visited));
+        visited
+    }
+
+    /// Checks whether the guard leaves the lab with an `extra` obstacle, jumping from obstacle
+    /// to obstacle. `seen` is cleared and used to detect loops.
+    pub fn escapes(&self, start: Guard, extra: Option<(usize, usize)>, seen: &mut BitSet) -> bool {
+        seen.clear();
+        let mut guard = start;
+
+        loop {
+            if !seen.insert(self.state(guard)) {
+                return false;
+            }
+            match self.jump(guard, extra) {
+                Some(next) => guard = next,
+                None => return true,
             }
         }
     }
-    true
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut puzzle = parse_input(input);
-    let mut seen_locations: HashSet<(usize, usize)> = HashSet::new();
+/// A fixed-size set of indices, stored as one bit per index.
+pub struct BitSet {
+    words: Vec<u64>,
+}
 
-    while let Some((guard_x, guard_y)) = puzzle.guard_position {
-        seen_locations.insert((guard_x, guard_y));
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
 
-        match puzzle.guard_direction {
-            Direction::North => {
-                puzzle.guard_position = guard_y.checked_sub(1).map(|new_y| (guard_x, new_y));
-            }
-            Direction::South => {
-                puzzle.guard_position = guard_y.checked_add(1).and_then(|new_y| {
-                    if new_y >= puzzle.map.len() {
-                        None
-                    } else {
-                        Some((guard_x, new_y))
-                    }
-                });
-            }
-            Direction::West => {
-                puzzle.guard_position = guard_x.checked_sub(1).map(|new_x| (new_x, guard_y))
-            }
-            Direction::East => {
-                puzzle.guard_position = guard_x.checked_add(1).and_then(|new_x| {
-                    if new_x >= puzzle.map[0].len() {
-                        None
-                    } else {
-                        Some((new_x, guard_y))
-                    }
-                });
-            }
+    /// Adds `idx` to the set. Returns whether it was not present yet.
+    pub fn insert(&mut self, idx: usize) -> bool {
+        let (word, bit) = (idx / 64, 1 << (idx % 64));
+        let is_new = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        is_new
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+}
+
+pub fn parse_input(input: &str) -> (Lab, Guard) {
+    let width = input.lines().next().map_or(0, str::len);
+    let mut obstacles: Vec<bool> = vec![];
+    let mut start = None;
+    for (line_idx, line) in input.lines().enumerate() {
+        if let Some(starting_col) = line.find('^') {
+            start = Some((starting_col, line_idx));
         }
-        if let Some((new_x, new_y)) = puzzle.guard_position {
-            if puzzle.map[new_y][new_x] == '.' {
-                puzzle.map[new_y][new_x] = '^';
-                puzzle.map[guard_y][guard_x] = '.';
-            } else if puzzle.map[new_y][new_x] == '#' {
-                puzzle.guard_position = Some((guard_x, guard_y));
-                puzzle.guard_direction = match puzzle.guard_direction {
-                    Direction::North => Direction::East,
-                    Direction::East => Direction::South,
-                    Direction::South => Direction::West,
-                    Direction::West => Direction::North,
-                };
-            }
+        obstacles.extend(line.chars().map(|c| c == '#'));
+    }
+    let height = obstacles.len() / width.max(1);
+    let guard = Guard {
+        position: start.expect("The map should contain the guard"),
+        direction: Direction::North,
+    };
+    (Lab::new(width, height, obstacles), guard)
+}
+
+/// Generates a square map with `size` times the area of the real 130x130 input and a similar
+/// density of obstacles.
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let side = (130.0 * (size as f64).sqrt()).round() as usize;
+    let guard = (rng.index(side), rng.index(side));
+
+    let mut map = String::with_capacity(side * (side + 1));
+    for y in 0..side {
+        for x in 0..side {
+            map.push(match (x, y) {
+                position if position == guard => '^',
+                _ if rng.range(0..=99) < 5 => '#',
+                _ => '.',
+            });
         }
+        map.push('\n');
     }
+    map
+}
 
-    Some(seen_locations.len().try_into().unwrap())
+pub fn part_one(input: &str) -> Option<u32> {
+    let (lab, start) = parse_input(input);
+    let visited = lab.walk(start);
+    Some(visited.iter().filter(|&&v| v).count().try_into().unwrap())
+}
+
+/// Renders the lab with the path walked by the guard so far.
+fn render_path(lab: &Lab, guard: Guard, visited: &[bool]) -> Frame {
+    let count = visited.iter().filter(|&&v| v).count();
+    Frame::from_fn(
+        format!("{count} positions visited"),
+        lab.width,
+        lab.height,
+        |x, y| {
+            if (x, y) == guard.position {
+                '^'
+            } else if lab.obstacles[lab.index((x, y))] {
+                '#'
+            } else if visited[lab.index((x, y))] {
+                'X'
+            } else {
+                '.'
+            }
+        },
+    )
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let puzzle = parse_input(input);
-    let accum: u32 = puzzle
-        .map
-        .par_iter()
+    let (lab, start) = parse_input(input);
+    // an obstacle off the path of part one never meets the guard.
+    let candidates: Vec<(usize, usize)> = lab
+        .walk(start)
+        .iter()
         .enumerate()
-        .map(|(line_idx, line)| {
-            line.iter()
-                .enumerate()
-                .filter(|(_, &c)| c == '.')
-                .map(|(col_idx, _)| {
-                    let mut new_puzzle = puzzle.clone();
-                    new_puzzle.map[line_idx][col_idx] = '#';
-                    if !can_escapce(new_puzzle) {
-                        1
-                    } else {
-                        0
-                    }
-                })
-                .sum::<u32>()
-        })
-        .sum();
-    Some(accum)
+        .filter(|&(idx, &visited)| visited && lab.position(idx) != start.position)
+        .map(|(idx, _)| lab.position(idx))
+        .collect();
+
+    let loops = candidates
+        .par_iter()
+        .map_init(
+            || BitSet::new(lab.cells() * 4),
+            |seen, &obstacle| !lab.escapes(start, Some(obstacle), seen),
+        )
+        .filter(|&is_loop| is_loop)
+        .count();
+    Some(loops.try_into().unwrap())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::Differential;
 
     #[test]
     fn test_part_one() {
@@ -192,4 +338,72 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    /// Tries an obstacle on every free cell and walks cell by cell, without jump tables.
+    fn part_two_naive(input: &str) -> u32 {
+        let (lab, start) = parse_input(input);
+        let mut seen = BitSet::new(lab.cells() * 4);
+        let mut loops = 0;
+
+        for idx in 0..lab.cells() {
+            let obstacle = lab.position(idx);
+            if lab.obstacles[idx] || obstacle == start.position {
+                continue;
+            }
+            seen.clear();
+            let mut guard = Some(start);
+            while let Some(current) = guard {
+                if !seen.insert(lab.state(current)) {
+                    loops += 1;
+                    break;
+                }
+                guard = lab.step(current, Some(obstacle));
+            }
+        }
+
+        loops
+    }
+
+    /// Renders `cells` as rows of `width` cells, with the guard at index `guard`.
+    fn to_map((width, cells, guard): &(usize, Vec<bool>, usize)) -> String {
+        cells
+            .chunks_exact(*width)
+            .enumerate()
+            .map(|(y, row)| {
+                let mut line: String = row.iter().map(|&c| if c { '#' } else { '.' }).collect();
+                if guard / width == y {
+                    line.replace_range(guard % width..=guard % width, "^");
+                }
+                line + "\n"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_part_two_matches_naive() {
+        Differential::new(|rng| {
+            let width = rng.range(1..=10) as usize;
+            let cells = rng.vec(width..=width * 10, |rng| rng.range(0..=5) == 0);
+            let guard = rng.index(cells.len());
+            (width, cells, guard)
+        })
+        .assume(|map| {
+            let (width, cells, guard) = map;
+            let is_valid = *width > 0
+                && cells.len() >= *width
+                && *guard < cells.len() / width * width
+                && !cells[*guard];
+            // like in the puzzle, the guard has to leave the lab without an extra obstacle.
+            is_valid && {
+                let (lab, start) = parse_input(&to_map(map));
+                lab.escapes(start, None, &mut BitSet::new(lab.cells() * 4))
+            }
+        })
+        // most random maps have no loops at all, so check more of them.
+        .cases(1000)
+        .check(
+            |map| part_two_naive(&to_map(map)),
+            |map| part_two(&to_map(map)).unwrap(),
+        );
+    }
 }
@@ -1,21 +1,56 @@
-advent_of_code::solution!(7);
+advent_of_code::solution!(7, variants {
+    1 => [backward = part_one, forward = part_one_forward],
+    2 => [backward = part_two, forward = part_two_forward],
+});
 
+use advent_of_code::math::{checked_concat, strip_digits};
 use rayon::prelude::*;
 
 #[derive(Debug)]
 pub struct Equation {
     result: u64,
     numbers: Vec<u64>,
-    operations: Vec<Operator>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Operator {
     Add,
     Mult,
     Concat,
 }
 
+/// An operator that can be undone, which lets the solver work backwards from the result.
+/// Implement it for another operator set to reuse [`is_solvable`].
+pub trait Operation {
+    /// Computes `lhs op rhs`, or `None` on overflow.
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64>;
+
+    /// Finds `lhs` such that `lhs op rhs == result`, or `None` if there is no such `lhs`.
+    fn undo(&self, result: u64, rhs: u64) -> Option<u64>;
+}
+
+impl Operation for Operator {
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        match self {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Mult => lhs.checked_mul(rhs),
+            Operator::Concat => checked_concat(lhs, rhs),
+        }
+    }
+
+    fn undo(&self, result: u64, rhs: u64) -> Option<u64> {
+        match self {
+            Operator::Add => result.checked_sub(rhs),
+            // NOTE: a zero `rhs` would allow any `lhs`, the puzzle inputs do not contain zeros.
+            Operator::Mult => (rhs != 0 && result.is_multiple_of(rhs)).then(|| result / rhs),
+            Operator::Concat => strip_digits(result, rhs),
+        }
+    }
+}
+
+pub const PART_ONE_OPERATORS: [Operator; 2] = [Operator::Add, Operator::Mult];
+pub const PART_TWO_OPERATORS: [Operator; 3] = [Operator::Add, Operator::Mult, Operator::Concat];
+
 pub fn parse_input(input: &str) -> Vec<Equation> {
     let mut eq_list: Vec<Equation> = vec![];
     for line in input.lines() {
@@ -32,122 +67,89 @@ pub fn parse_input(input: &str) -> Vec<Equation> {
             .filter(|x| !x.is_empty())
             .map(|x| x.parse::<u64>().expect("We should have a number"))
             .collect();
-        eq_list.push(Equation {
-            result,
-            numbers,
-            operations: vec![],
-        });
+        eq_list.push(Equation { result, numbers });
     }
     eq_list
 }
 
-pub fn create_all_permutations(
-    list: Vec<Vec<Operator>>,
-    len: usize,
-    use_concat: bool,
-) -> Vec<Vec<Operator>> {
-    if len == 0 {
-        return list;
+/// Checks whether inserting `operators` between the numbers, evaluated left to right, gives the
+/// result. Works right to left: every operator that can be undone for the last number leaves a
+/// smaller equation, and branches that can't be undone are pruned right away.
+pub fn is_solvable<O: Operation>(equation: &Equation, operators: &[O]) -> bool {
+    fn solve<O: Operation>(result: u64, numbers: &[u64], operators: &[O]) -> bool {
+        match numbers {
+            [] => false,
+            [first] => *first == result,
+            [rest @ .., last] => operators.iter().any(|operator| {
+                operator
+                    .undo(result, *last)
+                    .is_some_and(|lhs| solve(lhs, rest, operators))
+            }),
+        }
     }
 
-    let mut new_list: Vec<Vec<Operator>> = vec![];
-
-    for comb in list {
-        // Create the add
-        let mut new_internal_list = comb.clone();
-        new_internal_list.extend(vec![Operator::Add]);
-        new_list.push(new_internal_list);
-
-        // Create the mult
-        new_internal_list = comb.clone();
-        new_internal_list.extend(vec![Operator::Mult]);
-        new_list.push(new_internal_list);
+    solve(equation.result, &equation.numbers, operators)
+}
 
-        if use_concat {
-            // Create the mult
-            new_internal_list = comb.clone();
-            new_internal_list.extend(vec![Operator::Concat]);
-            new_list.push(new_internal_list);
+/// Like [`is_solvable`], but tries every combination of operators left to right.
+pub fn is_solvable_forward<O: Operation>(equation: &Equation, operators: &[O]) -> bool {
+    fn solve<O: Operation>(value: u64, result: u64, numbers: &[u64], operators: &[O]) -> bool {
+        match numbers {
+            [] => value == result,
+            [next, rest @ ..] => operators.iter().any(|operator| {
+                operator
+                    .apply(value, *next)
+                    .is_some_and(|value| solve(value, result, rest, operators))
+            }),
         }
     }
-    create_all_permutations(new_list, len - 1, use_concat)
+
+    match equation.numbers.split_first() {
+        Some((first, rest)) => solve(*first, equation.result, rest, operators),
+        None => false,
+    }
+}
+
+pub fn calibration_result<O: Operation + Sync>(
+    input: &str,
+    operators: &[O],
+    solver: fn(&Equation, &[O]) -> bool,
+) -> u64 {
+    parse_input(input)
+        .par_iter()
+        .filter(|equation| solver(equation, operators))
+        .map(|equation| equation.result)
+        .sum()
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let mut accum: u64 = 0;
-    let equations = parse_input(input);
-    for equation in equations {
-        // For every equation create all possible combination of the operators
-        let all_operators =
-            create_all_permutations(vec![vec![]], equation.numbers.len() - 1, false);
-
-        // In parallel, evaluate all equations to see if one will get to the result
-        let matching_comb = all_operators
-            .par_iter()
-            .map(|x| {
-                let mut result: u64 = equation.numbers[0];
-                for (idx, operator) in x.iter().enumerate() {
-                    result = match operator {
-                        Operator::Add => result + equation.numbers[idx + 1],
-                        Operator::Mult => result * equation.numbers[idx + 1],
-                        Operator::Concat => panic!("We should not have concat in part one"),
-                    }
-                }
-                result
-            })
-            .filter(|x| *x == equation.result)
-            .count();
-        if matching_comb > 0 {
-            accum += equation.result
-        }
-    }
+    Some(calibration_result(input, &PART_ONE_OPERATORS, is_solvable))
+}
 
-    Some(accum)
+pub fn part_one_forward(input: &str) -> Option<u64> {
+    Some(calibration_result(
+        input,
+        &PART_ONE_OPERATORS,
+        is_solvable_forward,
+    ))
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let equations = parse_input(input);
-    let total = equations
-        .par_iter()
-        .map(|x| {
-            // For every equation create all possible combination of the operators
-            let all_operators = create_all_permutations(vec![vec![]], x.numbers.len() - 1, true);
-            // println!("All operations combinations: {:?}", all_operators);
-
-            // In parallel, evaluate all equations to see if one will get to the result
-            let matching_comb = all_operators
-                .par_iter()
-                .map(|comb| {
-                    let mut result: u64 = x.numbers[0];
-                    for (idx, operator) in comb.iter().enumerate() {
-                        result = match operator {
-                            Operator::Add => result + x.numbers[idx + 1],
-                            Operator::Mult => result * x.numbers[idx + 1],
-                            Operator::Concat => (result.to_string()
-                                + &x.numbers[idx + 1].to_string())
-                                .parse()
-                                .expect("We should still have a number"),
-                        };
-                    }
-                    result
-                })
-                .filter(|y| *y == x.result)
-                .count();
-            if matching_comb > 0 {
-                // println!("Found a solution for {:?}", equation);
-                x.result
-            } else {
-                0
-            }
-        })
-        .sum();
+    Some(calibration_result(input, &PART_TWO_OPERATORS, is_solvable))
+}
 
-    Some(total)
+pub fn part_two_forward(input: &str) -> Option<u64> {
+    Some(calibration_result(
+        input,
+        &PART_TWO_OPERATORS,
+        is_solvable_forward,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::Differential;
 
     #[test]
     fn test_part_one() {
@@ -160,4 +162,67 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(11387));
     }
+
+    #[test]
+    fn test_forward_variants() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(part_one_forward(&input), Some(3749));
+        assert_eq!(part_two_forward(&input), Some(11387));
+    }
+
+    /// Subtraction, to check that other operator sets work with the backward solver.
+    struct Sub;
+
+    impl Operation for Sub {
+        fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+            lhs.checked_sub(rhs)
+        }
+
+        fn undo(&self, result: u64, rhs: u64) -> Option<u64> {
+            result.checked_add(rhs)
+        }
+    }
+
+    #[test]
+    fn test_custom_operators() {
+        let equation = Equation {
+            result: 3,
+            numbers: vec![10, 4, 3],
+        };
+        assert!(is_solvable(&equation, &[Sub]));
+        assert!(!is_solvable(&equation, &PART_TWO_OPERATORS));
+    }
+
+    #[test]
+    fn test_backward_matches_forward() {
+        Differential::new(|rng| {
+            let numbers = rng.vec(1..=6, |rng| rng.range(1..=20) as u64);
+            // pick a reachable result half of the time, the solvers must agree on both.
+            let result = if rng.bool() {
+                numbers.iter().skip(1).fold(numbers[0], |value, &n| {
+                    PART_TWO_OPERATORS[rng.index(3)].apply(value, n).unwrap()
+                })
+            } else {
+                rng.range(1..=2000) as u64
+            };
+            (result, numbers)
+        })
+        .assume(|(_, numbers)| !numbers.is_empty() && numbers.iter().all(|&n| n > 0))
+        .check(
+            |(result, numbers)| {
+                let equation = Equation {
+                    result: *result,
+                    numbers: numbers.clone(),
+                };
+                is_solvable_forward(&equation, &PART_TWO_OPERATORS)
+            },
+            |(result, numbers)| {
+                let equation = Equation {
+                    result: *result,
+                    numbers: numbers.clone(),
+                };
+                is_solvable(&equation, &PART_TWO_OPERATORS)
+            },
+        );
+    }
 }
@@ -1,3 +1,4 @@
+//! tags: grid
 advent_of_code::solution!(8);
 
 use std::collections::HashMap;
@@ -1,176 +1,224 @@
-use std::ops::Index;
+advent_of_code::solution!(9, generator = generate);
 
-advent_of_code::solution!(9);
+use advent_of_code::template::differential::Rng;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Display;
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Block {
-    File(u32),
-    Free,
+/// A run of consecutive blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub len: usize,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum SizedBlock {
-    File((u32, u32)),
-    Free(u32),
+/// A run of blocks of a single file. Compacting block by block splits files into several pieces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilePiece {
+    pub id: u32,
+    pub span: Span,
 }
 
-pub fn parse_input(input: &str) -> Vec<Block> {
-    let mut file_system: Vec<Block> = vec![];
-    for (index, size) in input.trim().chars().enumerate() {
-        if index % 2 == 0 {
-            for _ in 0..size.to_digit(10).expect("Should be a number") {
-                file_system.push(Block::File(index as u32 / 2));
+/// The disk as spans of files and free space, without expanding it to single blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskMap {
+    /// File pieces, highest id first.
+    files: Vec<FilePiece>,
+    /// Free spans between the files, from left to right.
+    free: Vec<Span>,
+    /// Number of blocks of the disk.
+    len: usize,
+}
+
+impl DiskMap {
+    /// Moves single blocks from the end of the disk into the leftmost free block until there are
+    /// no gaps left.
+    pub fn compact_blocks(&self) -> DiskMap {
+        let mut free = self.free.iter().copied().filter(|span| span.len > 0);
+        let mut gap = free.next();
+        let mut files = vec![];
+
+        for piece in &self.files {
+            let mut piece = *piece;
+            // move blocks from the end of the file for as long as there is a gap to its left.
+            while let Some(span) = gap.filter(|span| span.start < piece.span.start) {
+                let moved = span.len.min(piece.span.len);
+                files.push(FilePiece {
+                    id: piece.id,
+                    span: Span {
+                        start: span.start,
+                        len: moved,
+                    },
+                });
+                piece.span.len -= moved;
+                gap = if moved == span.len {
+                    free.next()
+                } else {
+                    Some(Span {
+                        start: span.start + moved,
+                        len: span.len - moved,
+                    })
+                };
+                if piece.span.len == 0 {
+                    break;
+                }
             }
-        } else {
-            for _ in 0..size.to_digit(10).expect("Should be a number") {
-                file_system.push(Block::Free);
+            if piece.span.len > 0 {
+                files.push(piece);
             }
         }
-    }
 
-    file_system
-}
+        self.with_files(files)
+    }
 
-pub fn parse_input_sized_blocks(input: &str) -> Vec<SizedBlock> {
-    let mut file_system: Vec<SizedBlock> = vec![];
-    for (index, size) in input.trim().chars().enumerate() {
-        let size = size.to_digit(10).expect("This should be a number");
-        if index % 2 == 0 {
-            let fileidx = index as u32 / 2;
-            file_system.push(SizedBlock::File((fileidx, size)));
-        } else {
-            file_system.push(SizedBlock::Free(size));
+    /// Moves whole files, highest id first, into the leftmost free span that fits them.
+    ///
+    /// Free spans are indexed by their length in min-heaps of their start, so finding the
+    /// leftmost fitting span only looks at the leftmost span of every length.
+    pub fn compact_files(&self) -> DiskMap {
+        let max_len = self.free.iter().map(|span| span.len).max().unwrap_or(0);
+        let mut by_len: Vec<BinaryHeap<Reverse<usize>>> = vec![BinaryHeap::new(); max_len + 1];
+        for span in self.free.iter().filter(|span| span.len > 0) {
+            by_len[span.len].push(Reverse(span.start));
         }
-    }
 
-    file_system
-}
+        let files = self
+            .files
+            .iter()
+            .map(|piece| {
+                let target = (piece.span.len..=max_len)
+                    .filter_map(|len| by_len[len].peek().map(|start| (start.0, len)))
+                    .filter(|&(start, _)| start < piece.span.start)
+                    .min();
 
-pub fn defrag_by_block(file_system: Vec<Block>) -> Vec<Block> {
-    let mut defrag_file_system = file_system.clone();
-    let mut scan_idx = defrag_file_system.len() - 1;
-    let mut insert_idx = 0;
+                let Some((start, len)) = target else {
+                    return *piece;
+                };
 
-    while insert_idx < scan_idx {
-        if defrag_file_system[scan_idx] == Block::Free {
-            scan_idx -= 1;
-            continue;
-        }
-        match defrag_file_system[insert_idx] {
-            Block::File(_) => {}
-            Block::Free => {
-                defrag_file_system.swap(insert_idx, scan_idx);
-                scan_idx -= 1;
-            }
-        }
-        insert_idx += 1;
+                by_len[len].pop();
+                let rest = len - piece.span.len;
+                if rest > 0 {
+                    by_len[rest].push(Reverse(start + piece.span.len));
+                }
+                // NOTE: the space freed by the file is right of all files that are still to move.
+                FilePiece {
+                    id: piece.id,
+                    span: Span {
+                        start,
+                        len: piece.span.len,
+                    },
+                }
+            })
+            .collect();
+
+        self.with_files(files)
     }
 
-    defrag_file_system
-}
+    /// A disk of the same size with the files at new positions.
+    fn with_files(&self, mut files: Vec<FilePiece>) -> DiskMap {
+        files.sort_unstable_by_key(|piece| Reverse(piece.span.start));
 
-pub fn defrag_by_file(file_system: Vec<SizedBlock>) -> Vec<SizedBlock> {
-    let mut defrag_file_system = file_system.clone();
-
-    let mut scan_idx = defrag_file_system.len();
-
-    while scan_idx > 0 {
-        scan_idx -= 1;
-        if let SizedBlock::File((fileidx, filesize)) = defrag_file_system[scan_idx] {
-            let mut insert_idx = 0;
-            while insert_idx < scan_idx {
-                match defrag_file_system[insert_idx] {
-                    SizedBlock::File(_) => {}
-                    SizedBlock::Free(size) => {
-                        if size >= filesize {
-                            // Replace the file at scan index with a free block
-                            defrag_file_system[scan_idx] = SizedBlock::Free(filesize);
-                            // Mut the free block to remove the space (or remove it completely if
-                            // all the free space was taken)
-                            if size == filesize {
-                                defrag_file_system.remove(insert_idx);
-                            } else {
-                                defrag_file_system[insert_idx] = SizedBlock::Free(size - filesize);
-                            }
-                            // Insert a new file block at current insert index
-                            defrag_file_system
-                                .insert(insert_idx, SizedBlock::File((fileidx, filesize)));
-                            break;
-                        }
-                    }
-                }
-                insert_idx += 1
-            }
+        let mut free = vec![];
+        let mut end = self.len;
+        for piece in &files {
+            let piece_end = piece.span.start + piece.span.len;
+            free.push(Span {
+                start: piece_end,
+                len: end - piece_end,
+            });
+            end = piece.span.start;
+        }
+        free.push(Span { start: 0, len: end });
+        free.retain(|span| span.len > 0);
+        free.reverse();
+
+        DiskMap {
+            files,
+            free,
+            len: self.len,
         }
     }
 
-    defrag_file_system
+    /// Sum of the position of every block times the id of its file.
+    pub fn checksum(&self) -> u64 {
+        self.files
+            .iter()
+            .map(|piece| {
+                let (start, len) = (piece.span.start as u64, piece.span.len as u64);
+                // sum of the positions start..start + len
+                let positions = len * start + len * len.saturating_sub(1) / 2;
+                u64::from(piece.id) * positions
+            })
+            .sum()
+    }
 }
 
-pub fn convert_to_block_list(file_system: Vec<SizedBlock>) -> Vec<Block> {
-    let mut block_file_system: Vec<Block> = vec![];
-    for blocks in file_system.iter() {
-        match blocks {
-            SizedBlock::File((fileidx, filesize)) => {
-                for _ in 0..*filesize {
-                    block_file_system.push(Block::File(*fileidx))
-                }
-            }
-            SizedBlock::Free(size) => {
-                for _ in 0..*size {
-                    block_file_system.push(Block::Free)
-                }
-            }
+/// Renders the disk in the puzzle's notation, e.g. `00...111...2`. Like in the puzzle, file ids
+/// are shown with their last digit.
+impl Display for DiskMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut blocks = vec!['.'; self.len];
+        for piece in &self.files {
+            let digit = char::from_digit(piece.id % 10, 10).unwrap();
+            blocks[piece.span.start..piece.span.start + piece.span.len].fill(digit);
         }
+        write!(f, "{}", blocks.into_iter().collect::<String>())
     }
-
-    block_file_system
 }
 
-pub fn compute_checksum(file_system: Vec<Block>) -> u64 {
-    let mut checksum: u64 = 0;
-    for (idx, block) in file_system.iter().enumerate() {
-        if let Block::File(file_idx) = block {
-            checksum += *file_idx as u64 * idx as u64;
+pub fn parse_input(input: &str) -> DiskMap {
+    let mut files: Vec<FilePiece> = vec![];
+    let mut free: Vec<Span> = vec![];
+    let mut start = 0;
+
+    for (index, size) in input.trim().chars().enumerate() {
+        let len = size.to_digit(10).expect("This should be a number") as usize;
+        let span = Span { start, len };
+        if index % 2 == 0 {
+            files.push(FilePiece {
+                id: index as u32 / 2,
+                span,
+            });
+        } else {
+            free.push(span);
         }
+        start += len;
+    }
+
+    files.reverse();
+    DiskMap {
+        files,
+        free,
+        len: start,
     }
-    checksum
 }
 
-pub fn compute_checksum_block_file_system(file_system: Vec<SizedBlock>) -> u64 {
-    let mut checksum: u64 = 0;
-    let mut block_base_idx: u64 = 0;
-    for block in file_system.iter() {
-        match block {
-            SizedBlock::File((fileidx, size)) => {
-                for i in block_base_idx..block_base_idx + *size as u64 {
-                    checksum += i * *fileidx as u64;
-                }
-                block_base_idx += *size as u64;
-            }
-            SizedBlock::Free(size) => {
-                block_base_idx += *size as u64;
-            }
+/// Generates a disk map with `size * 10_000` files, the real input has 10,000.
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut disk_map = String::with_capacity(size * 20_000);
+    for file in 0..size * 10_000 {
+        if file > 0 {
+            disk_map.push_str(&rng.range(0..=9).to_string());
         }
+        disk_map.push_str(&rng.range(1..=9).to_string());
     }
-    checksum
+    disk_map.push('\n');
+    disk_map
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let file_system = parse_input(input);
-    let new_file_system = defrag_by_block(file_system);
-    Some(compute_checksum(new_file_system))
+    Some(parse_input(input).compact_blocks().checksum())
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let file_system = parse_input_sized_blocks(input);
-    let new_file_system = defrag_by_file(file_system);
-    Some(compute_checksum_block_file_system(new_file_system))
+    Some(parse_input(input).compact_files().checksum())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::Differential;
 
     #[test]
     fn test_part_one() {
@@ -183,4 +231,115 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2858));
     }
+
+    #[test]
+    fn test_display() {
+        let disk = parse_input(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(
+            disk.to_string(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+        assert_eq!(
+            disk.compact_blocks().to_string(),
+            "0099811188827773336446555566.............."
+        );
+        assert_eq!(
+            disk.compact_files().to_string(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+    }
+
+    /// Renders `(file, free)` size pairs as a disk map.
+    fn to_disk_map(files: &[(u8, u8)]) -> String {
+        files
+            .iter()
+            .map(|(file, free)| format!("{file}{free}"))
+            .collect()
+    }
+
+    /// Expands a disk map to single blocks, with the file id of every block.
+    fn to_blocks(input: &str) -> Vec<Option<u32>> {
+        input
+            .trim()
+            .chars()
+            .enumerate()
+            .flat_map(|(index, size)| {
+                let id = (index % 2 == 0).then_some(index as u32 / 2);
+                vec![id; size.to_digit(10).unwrap() as usize]
+            })
+            .collect()
+    }
+
+    fn checksum(blocks: &[Option<u32>]) -> u64 {
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| id.map_or(0, |id| idx as u64 * u64::from(id)))
+            .sum()
+    }
+
+    /// Moves single blocks from the end into the leftmost free block, one at a time.
+    fn compact_blocks_naive(input: &str) -> u64 {
+        let mut blocks = to_blocks(input);
+        loop {
+            let free = blocks.iter().position(Option::is_none);
+            let file = blocks.iter().rposition(Option::is_some);
+            match (free, file) {
+                (Some(free), Some(file)) if free < file => blocks.swap(free, file),
+                _ => return checksum(&blocks),
+            }
+        }
+    }
+
+    /// Moves whole files, highest id first, into the leftmost span of free blocks that fits them.
+    fn compact_files_naive(input: &str) -> u64 {
+        let mut blocks = to_blocks(input);
+        let max_id = blocks.iter().flatten().max().copied().unwrap_or(0);
+
+        for id in (0..=max_id).rev() {
+            let start = blocks.iter().position(|b| *b == Some(id)).unwrap();
+            let len = blocks[start..]
+                .iter()
+                .take_while(|b| **b == Some(id))
+                .count();
+            let target = (0..start).find(|&idx| blocks[idx..idx + len].iter().all(Option::is_none));
+            if let Some(target) = target {
+                for offset in 0..len {
+                    blocks.swap(target + offset, start + offset);
+                }
+            }
+        }
+
+        checksum(&blocks)
+    }
+
+    fn differential() -> Differential<'static, Vec<(u8, u8)>> {
+        Differential::new(|rng| {
+            rng.vec(1..=12, |rng| {
+                (rng.range(1..=9) as u8, rng.range(0..=9) as u8)
+            })
+        })
+        .assume(|files| {
+            !files.is_empty()
+                && files
+                    .iter()
+                    .all(|&(file, free)| (1..=9).contains(&file) && free <= 9)
+        })
+    }
+
+    #[test]
+    fn test_part_one_matches_naive() {
+        differential().check(
+            |files| compact_blocks_naive(&to_disk_map(files)),
+            |files| part_one(&to_disk_map(files)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_part_two_matches_naive() {
+        differential().check(
+            |files| compact_files_naive(&to_disk_map(files)),
+            |files| part_two(&to_disk_map(files)).unwrap(),
+        );
+    }
 }
@@ -1,4 +1,6 @@
+//! tags: grid
 advent_of_code::solution!(10);
+use advent_of_code::memo::ShardedMemo;
 use rayon::prelude::*;
 use std::collections::HashSet;
 
@@ -14,7 +16,7 @@ pub fn parse_input(input: &str) -> Vec<Vec<u8>> {
         .collect()
 }
 
-pub fn find_trail_head(puzzle: &Vec<Vec<u8>>) -> Vec<(usize, usize)> {
+pub fn find_trail_head(puzzle: &[Vec<u8>]) -> Vec<(usize, usize)> {
     let mut trail_heads: Vec<(usize, usize)> = vec![];
     for (y, line) in puzzle.iter().enumerate() {
         for (x, val) in line.iter().enumerate() {
@@ -28,7 +30,7 @@ pub fn find_trail_head(puzzle: &Vec<Vec<u8>>) -> Vec<(usize, usize)> {
 
 pub fn find_trailends(
     trailhead: (usize, usize),
-    puzzle: &Vec<Vec<u8>>,
+    puzzle: &[Vec<u8>],
     trailhead_val: u8,
 ) -> Vec<(usize, usize)> {
     let mut trailends: Vec<(usize, usize)> = vec![];
@@ -73,6 +75,33 @@ pub fn find_trailends(
     trailends
 }
 
+/// Number of trails from a position to any height 9, for one grid.
+pub type Ratings = ShardedMemo<(usize, usize), u32>;
+
+/// Counts the distinct trails from `position` to any height 9.
+pub fn trail_rating(position: (usize, usize), puzzle: &[Vec<u8>], ratings: &Ratings) -> u32 {
+    let (x, y) = position;
+    let height = puzzle[y][x];
+    if height == 9 {
+        return 1;
+    }
+
+    ratings.get_or_insert_with(position, || {
+        let neighbours = [
+            x.checked_sub(1).map(|x| (x, y)),
+            (x + 1 < puzzle[y].len()).then_some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            (y + 1 < puzzle.len()).then_some((x, y + 1)),
+        ];
+        neighbours
+            .into_iter()
+            .flatten()
+            .filter(|&(nx, ny)| puzzle[ny][nx] == height + 1)
+            .map(|next| trail_rating(next, puzzle, ratings))
+            .sum()
+    })
+}
+
 pub fn uniquify_ends(ends: Vec<(usize, usize)>) -> HashSet<(usize, usize)> {
     let mut trail_ends_unique: HashSet<(usize, usize)> = HashSet::new();
     for end in ends.iter() {
@@ -97,11 +126,12 @@ pub fn part_one(input: &str) -> Option<u32> {
 pub fn part_two(input: &str) -> Option<u32> {
     let puzzle = parse_input(input);
     let trail_heads = find_trail_head(&puzzle);
+    // trail heads share the ends of their trails, so the table is shared between the rayon threads.
+    let ratings = Ratings::default();
     Some(
         trail_heads
             .par_iter()
-            .map(|x| find_trailends(*x, &puzzle, 0).len())
-            .map(|x| x as u32)
+            .map(|x| trail_rating(*x, &puzzle, &ratings))
             .sum::<u32>(),
     )
 }
@@ -121,4 +151,12 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(81));
     }
+
+    #[test]
+    fn test_part_two_different_grids() {
+        // both grids have a trail head at (0, 0), but with different ratings.
+        assert_eq!(part_two("0123456789\n1234567898\n"), Some(10));
+        assert_eq!(part_two("0123456789\n9999999999\n"), Some(2));
+        assert_eq!(part_two("0123456789\n1234567898\n"), Some(10));
+    }
 }
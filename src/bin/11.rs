@@ -1,66 +1,122 @@
-advent_of_code::solution!(11);
+advent_of_code::solution!(11, report = report);
 
 use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock};
 
-type RockCache = Mutex<HashMap<(u64, u8), u64>>;
+use advent_of_code::math::{digit_count, split_digits};
+use advent_of_code::template::options;
 
-static GLOBAL_CACHE: OnceLock<RockCache> = OnceLock::new();
-
-pub fn get_cache() -> &'static RockCache {
-    GLOBAL_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
-}
-
-pub fn parse_input(input: &str) -> Vec<u64> {
-    input
-        .split_whitespace()
-        .map(|x| x.parse::<u64>().expect("All inputs must be numbers"))
-        .collect::<Vec<u64>>()
+/// The stones in a line, counted by engraved number. Their order never matters for the count.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stones {
+    counts: HashMap<u64, u128>,
 }
 
-pub fn cnt_new_rocks(num: u64, step: u8) -> u64 {
-    if step == 0 {
-        return 1;
+impl Stones {
+    pub fn parse(input: &str) -> Self {
+        let mut counts = HashMap::new();
+        for stone in input.split_whitespace() {
+            let stone = stone.parse().expect("All inputs must be numbers");
+            *counts.entry(stone).or_default() += 1;
+        }
+        Stones { counts }
     }
-    let cache = get_cache();
-    {
-        let cache_guard = cache.lock().expect("Failed getting the cache");
-        if let Some(cnt) = cache_guard.get(&(num, step)) {
-            return *cnt;
+
+    /// Applies the rules to every stone once. Returns `None` if the number of stones no longer
+    /// fits in a `u128`, which happens after about 200 blinks.
+    pub fn blink(&self) -> Option<Self> {
+        let mut counts: HashMap<u64, u128> = HashMap::with_capacity(self.counts.len() * 2);
+        let mut add = |stone, count| {
+            let entry = counts.entry(stone).or_default();
+            *entry = entry.checked_add(count)?;
+            Some(())
+        };
+
+        for (&stone, &count) in &self.counts {
+            let (first, second) = blink_stone(stone);
+            add(first, count)?;
+            if let Some(second) = second {
+                add(second, count)?;
+            }
         }
+
+        Some(Stones { counts })
+    }
+
+    /// Number of stones, or `None` if it does not fit in a `u128`.
+    pub fn total(&self) -> Option<u128> {
+        self.counts
+            .values()
+            .try_fold(0u128, |total, count| total.checked_add(*count))
     }
-    let res: u64;
-    let len = num.to_string().len();
-    if num == 0 {
-        res = cnt_new_rocks(1, step - 1);
-    } else if len.is_multiple_of(2) {
-        res = cnt_new_rocks(
-            num.to_string()[0..len / 2]
-                .parse::<u64>()
-                .expect("Should be a number"),
-            step - 1,
-        ) + cnt_new_rocks(
-            num.to_string()[len / 2..]
-                .parse::<u64>()
-                .expect("Should be a number"),
-            step - 1,
-        );
+
+    /// Number of different engraved numbers.
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+}
+
+/// The stone(s) that a stone turns into after a blink.
+pub fn blink_stone(stone: u64) -> (u64, Option<u64>) {
+    let digits = digit_count(stone);
+    if stone == 0 {
+        (1, None)
+    } else if digits.is_multiple_of(2) {
+        let (high, low) = split_digits(stone, digits / 2);
+        (high, Some(low))
     } else {
-        res = cnt_new_rocks(num * 2024, step - 1);
+        let stone = stone
+            .checked_mul(2024)
+            .expect("Engraved numbers should fit in a u64");
+        (stone, None)
     }
-    let mut cache_guard = cache.lock().unwrap();
-    cache_guard.insert((num, step), res);
-    res
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let puzzle = parse_input(input);
-    Some(puzzle.iter().map(|x| cnt_new_rocks(*x, 25)).sum())
+/// Blinks `blinks` times, calling `on_blink` with the blink number and the stones after it.
+/// Returns `None` if the number of stones overflows.
+pub fn simulate(
+    mut stones: Stones,
+    blinks: u32,
+    mut on_blink: impl FnMut(u32, &Stones),
+) -> Option<Stones> {
+    for blink in 1..=blinks {
+        stones = stones.blink()?;
+        on_blink(blink, &stones);
+    }
+    Some(stones)
+}
+
+/// Counts the stones after `blinks` blinks, or `None` if there are too many.
+pub fn count_stones(input: &str, blinks: u32) -> Option<u128> {
+    simulate(Stones::parse(input), blinks, |_, _| {})?.total()
+}
+
+pub fn part_one(input: &str) -> Option<u128> {
+    count_stones(input, 25)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let puzzle = parse_input(input);
-    Some(puzzle.iter().map(|x| cnt_new_rocks(*x, 75)).sum())
+pub fn part_two(input: &str) -> Option<u128> {
+    count_stones(input, 75)
+}
+
+/// Counts the stones after `--option blinks=N` blinks (75 by default). With `--option distinct`,
+/// prints the number of different stones after every blink.
+pub fn report(input: &str) {
+    let distinct = options::is_set("distinct");
+    if !distinct && !options::is_set("blinks") {
+        return;
+    }
+
+    let blinks = options::value("blinks").unwrap_or(75);
+    let stones = simulate(Stones::parse(input), blinks, |blink, stones| {
+        if distinct {
+            eprintln!("blink {blink}: {} distinct stones", stones.distinct());
+        }
+    });
+
+    match stones.and_then(|stones| stones.total()) {
+        Some(total) => eprintln!("{total} stones after {blinks} blinks"),
+        None => eprintln!("Too many stones to count after {blinks} blinks"),
+    }
 }
 
 #[cfg(test)]
@@ -73,9 +129,35 @@ mod tests {
         assert_eq!(result, Some(55312));
     }
 
-    // #[test]
-    // fn test_part_two() {
-    //     let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-    //     assert_eq!(result, None);
-    // }
+    #[test]
+    fn test_blink_stone() {
+        assert_eq!(blink_stone(0), (1, None));
+        assert_eq!(blink_stone(1000), (10, Some(0)));
+        assert_eq!(blink_stone(125), (253000, None));
+    }
+
+    #[test]
+    fn test_simulate() {
+        let stones = Stones::parse(&advent_of_code::template::read_file("examples", DAY));
+        let mut steps = vec![];
+        simulate(stones, 6, |_, stones| {
+            steps.push((stones.total().unwrap(), stones.distinct()))
+        });
+        // after 6 blinks: 2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
+        assert_eq!(steps.last(), Some(&(22, 15)));
+        assert_eq!(steps[0], (3, 3));
+    }
+
+    #[test]
+    fn test_many_blinks() {
+        // more blinks than the `u8` step of the old recursive solution could count.
+        let mut distinct = 0;
+        let stones = simulate(Stones::parse("0"), 180, |_, stones| {
+            distinct = stones.distinct()
+        });
+        assert!(stones.and_then(|stones| stones.total()).is_some());
+        assert_eq!(distinct, 54);
+
+        assert_eq!(count_stones("0", 300), None);
+    }
 }
@@ -1,5 +1,13 @@
-advent_of_code::solution!(13);
+advent_of_code::solution!(13, variants {
+    1 => [
+        equation = part_one,
+        walkback = part_one_walkback,
+        walkback_mult_only = part_one_walkback_mult_only,
+        search = part_one_search,
+    ],
+});
 
+use advent_of_code::math::solve_2x2;
 use rayon::prelude::*;
 use regex::Regex;
 use std::cmp::min;
@@ -53,33 +61,18 @@ pub fn parse_input(input: &str, prize_offset: Option<i64>) -> Vec<Machine> {
     machines
 }
 pub fn find_min_machine_solution_equation(machine: &Machine) -> i64 {
-    /* This is a 2 var 2 equations system... the formula can be proved on paper... here are the
-     * final equations:
-     * M = (TxYa-TyXa)/(XbYa-YbXa)
-     * N = (Tx-MXb) / Xa
-     * If M and N are not integer, we do not have a solution
-     */
+    // n presses of A and m presses of B must satisfy n*A + m*B = prize on both axes. This is a
+    // 2x2 linear system; without a non-negative integer solution the prize can't be won.
     let (tx, ty) = machine.prize;
     let (xa, ya) = machine.button_a_offset;
     let (xb, yb) = machine.button_b_offset;
-    let num: i64 = tx * ya - ty * xa;
-    let den: i64 = xb * ya - yb * xa;
-    // If den does not divide num, no solution with integers
-    if num % den != 0 {
+    let Some([n, m]) = solve_2x2([[xa, xb], [ya, yb]], [tx, ty]) else {
         return 0;
+    };
+    match (n.to_integer(), m.to_integer()) {
+        (Some(n), Some(m)) if n >= 0 && m >= 0 => m * BUTTON_B_PRICE + n * BUTTON_A_PRICE,
+        _ => 0,
     }
-    let m = num / den;
-
-    let num: i64 = tx - m * xb;
-    let den: i64 = xa;
-    if num % den != 0 {
-        return 0;
-    }
-    let n: i64 = num / den;
-    if m < 0 || n < 0 {
-        return 0;
-    }
-    m * BUTTON_B_PRICE + n * BUTTON_A_PRICE
 }
 
 pub fn find_min_machine_solution_walkback_mult_only(machine: &Machine) -> i64 {
@@ -92,20 +85,18 @@ pub fn find_min_machine_solution_walkback_mult_only(machine: &Machine) -> i64 {
     let (target_x, target_y) = machine.prize;
     let (a_offset_x, a_offset_y) = machine.button_a_offset;
     let (b_offset_x, b_offset_y) = machine.button_b_offset;
-    let mut number_of_b_moves: i64 = min(target_x / b_offset_x, target_y / b_offset_y)
-        .try_into()
-        .unwrap();
+    let mut number_of_b_moves: i64 = min(target_x / b_offset_x, target_y / b_offset_y);
     while number_of_b_moves >= 0 {
-        let coord_x = number_of_b_moves as i64 * b_offset_x as i64;
-        let coord_y = number_of_b_moves as i64 * b_offset_y as i64;
-        let delta_x = target_x as i64 - coord_x;
-        let delta_y = target_y as i64 - coord_y;
-        let mod_x_a = delta_x % a_offset_x as i64;
-        let mod_y_a = delta_y % a_offset_y as i64;
-        let div_x_a = delta_x / a_offset_x as i64;
-        let div_y_a = delta_y / a_offset_y as i64;
+        let coord_x = number_of_b_moves * b_offset_x;
+        let coord_y = number_of_b_moves * b_offset_y;
+        let delta_x = target_x - coord_x;
+        let delta_y = target_y - coord_y;
+        let mod_x_a = delta_x % a_offset_x;
+        let mod_y_a = delta_y % a_offset_y;
+        let div_x_a = delta_x / a_offset_x;
+        let div_y_a = delta_y / a_offset_y;
         if mod_x_a == 0 && mod_y_a == 0 && div_x_a == div_y_a {
-            return number_of_b_moves as i64 * BUTTON_B_PRICE + div_x_a * BUTTON_A_PRICE;
+            return number_of_b_moves * BUTTON_B_PRICE + div_x_a * BUTTON_A_PRICE;
         }
         number_of_b_moves -= 1;
     }
@@ -195,31 +186,39 @@ pub fn find_min_machine_solution(machine: &Machine) -> i64 {
     min_price
 }
 
+fn solve_with(input: &str, prize_offset: Option<i64>, solver: fn(&Machine) -> i64) -> Option<i64> {
+    let machines = parse_input(input, prize_offset);
+    Some(machines.par_iter().map(solver).sum())
+}
+
 pub fn part_one(input: &str) -> Option<i64> {
-    let machines = parse_input(input, None);
-    // Some(find_min_machine_solution_walkback_mult_only(&machines[1]))
-
-    Some(
-        machines
-            .par_iter()
-            .map(find_min_machine_solution_equation)
-            .sum(),
-    )
+    solve_with(input, None, find_min_machine_solution_equation)
+}
+
+pub fn part_one_walkback(input: &str) -> Option<i64> {
+    solve_with(input, None, find_min_machine_solution_walkback)
+}
+
+pub fn part_one_walkback_mult_only(input: &str) -> Option<i64> {
+    solve_with(input, None, find_min_machine_solution_walkback_mult_only)
+}
+
+pub fn part_one_search(input: &str) -> Option<i64> {
+    solve_with(input, None, find_min_machine_solution)
 }
 
 pub fn part_two(input: &str) -> Option<i64> {
-    let machines = parse_input(input, Some(10000000000000));
-    Some(
-        machines
-            .par_iter()
-            .map(find_min_machine_solution_equation)
-            .sum(),
+    solve_with(
+        input,
+        Some(10000000000000),
+        find_min_machine_solution_equation,
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::Differential;
 
     #[test]
     fn test_part_one() {
@@ -227,6 +226,60 @@ mod tests {
         assert_eq!(result, Some(480));
     }
 
+    #[test]
+    fn test_part_one_variants() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(part_one_walkback(&input), Some(480));
+        assert_eq!(part_one_walkback_mult_only(&input), Some(480));
+        assert_eq!(part_one_search(&input), Some(480));
+    }
+
+    type MachineSpec = (i64, i64, i64, i64, i64, i64);
+
+    fn to_machine(&(xa, ya, xb, yb, tx, ty): &MachineSpec) -> Machine {
+        Machine {
+            button_a_offset: (xa, ya),
+            button_b_offset: (xb, yb),
+            prize: (tx, ty),
+        }
+    }
+
+    fn check_against_search(solver: fn(&Machine) -> i64) {
+        Differential::new(|rng| {
+            let (xa, ya, xb, yb) = (
+                rng.range(1..=9),
+                rng.range(1..=9),
+                rng.range(1..=9),
+                rng.range(1..=9),
+            );
+            // random prizes are rarely reachable, so most are built from button presses.
+            if rng.bool() {
+                let (a, b) = (rng.range(0..=8), rng.range(0..=8));
+                (xa, ya, xb, yb, a * xa + b * xb, a * ya + b * yb)
+            } else {
+                (xa, ya, xb, yb, rng.range(0..=100), rng.range(0..=100))
+            }
+        })
+        .assume(|&(xa, ya, xb, yb, tx, ty)| {
+            xa > 0 && ya > 0 && xb > 0 && yb > 0 && tx >= 0 && ty >= 0 && xb * ya != yb * xa
+        })
+        .check(
+            |spec| find_min_machine_solution(&to_machine(spec)),
+            |spec| solver(&to_machine(spec)),
+        );
+    }
+
+    #[test]
+    fn test_equation_matches_search() {
+        check_against_search(find_min_machine_solution_equation);
+    }
+
+    #[test]
+    fn test_walkback_matches_search() {
+        check_against_search(find_min_machine_solution_walkback);
+        check_against_search(find_min_machine_solution_walkback_mult_only);
+    }
+
     // #[test]
     // fn test_part_two() {
     //     let result = part_two(&advent_of_code::template::read_file("examples", DAY));
@@ -1,4 +1,8 @@
-advent_of_code::solution!(14);
+//! tags: grid, simulation
+advent_of_code::solution!(14, generator = generate);
+use advent_of_code::math::{crt, wrap_add};
+use advent_of_code::template::differential::Rng;
+use advent_of_code::template::visualize::{self, Frame};
 use regex::Regex;
 
 #[derive(Debug, Clone, Copy)]
@@ -7,6 +11,21 @@ pub struct Robot {
     velocity: (isize, isize),
 }
 
+/// The area the robots move on. Robots that leave it on one side re-enter on the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Board {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Board {
+    /// The board of the real puzzle input. The example uses a smaller one.
+    pub const PUZZLE: Board = Board {
+        width: 101,
+        height: 103,
+    };
+}
+
 pub fn parse_input(input: &str) -> Vec<Robot> {
     let parse_regex = Regex::new(r"p=(\d+),(\d+) v=(-?\d+),(-?\d+)").unwrap();
 
@@ -28,99 +47,198 @@ pub fn parse_input(input: &str) -> Vec<Robot> {
         .collect::<Vec<Robot>>()
 }
 
-pub fn render_map(robots: Vec<Robot>) {
-    for y in 0..MAZE_HEIGHT {
-        let mut line: String = "".to_string();
-        for x in 0..MAZE_WIDTH {
-            if robots
-                .iter()
-                .filter(|robot| robot.position == (x, y))
-                .count()
-                > 0
-            {
-                line += "#";
-            } else {
-                line += ".";
-            }
-        }
-        println!("{}", line);
-    }
+/// Generates `size * 500` robots, the real input has 500.
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    (0..size * 500)
+        .map(|_| {
+            format!(
+                "p={},{} v={},{}\n",
+                rng.index(Board::PUZZLE.width),
+                rng.index(Board::PUZZLE.height),
+                rng.range(-99..=99),
+                rng.range(-99..=99)
+            )
+        })
+        .collect()
 }
 
-const MAZE_WIDTH: usize = 101;
-const MAZE_HEIGHT: usize = 103;
-pub fn simulate_robot(robot: Robot, seconds: usize) -> Robot {
-    let (init_robot_x, init_robot_y) = robot.position;
-    let (robot_vel_x, robot_vel_y) = robot.velocity;
-
-    // Calculate the full offset that should be applied
-    let final_x_offset = robot_vel_x * seconds as isize;
-    let final_y_offset = robot_vel_y * seconds as isize;
-    let final_x_position: usize = if final_x_offset >= 0 {
-        (init_robot_x + final_x_offset as usize) % (MAZE_WIDTH)
-    } else {
-        let final_x_offset = final_x_offset.unsigned_abs() % MAZE_WIDTH;
-        if final_x_offset <= init_robot_x {
-            init_robot_x - final_x_offset
-        } else {
-            let signed_x_position = init_robot_x as isize - final_x_offset as isize;
-            let abs_position: usize = signed_x_position.unsigned_abs();
-            MAZE_WIDTH - abs_position
-        }
-    };
-    let final_y_position: usize = if final_y_offset >= 0 {
-        (init_robot_y + final_y_offset as usize) % (MAZE_HEIGHT)
-    } else {
-        let final_y_offset = final_y_offset.unsigned_abs() % MAZE_HEIGHT;
-        if final_y_offset <= init_robot_y {
-            init_robot_y - final_y_offset
+pub fn render_map(robots: &[Robot], board: &Board, caption: String) -> Frame {
+    let mut occupied = vec![false; board.width * board.height];
+    for robot in robots {
+        occupied[robot.position.1 * board.width + robot.position.0] = true;
+    }
+    Frame::from_fn(caption, board.width, board.height, |x, y| {
+        if occupied[y * board.width + x] {
+            '#'
         } else {
-            let signed_y_position = init_robot_y as isize - final_y_offset as isize;
-            let abs_position: usize = signed_y_position.unsigned_abs();
-            MAZE_HEIGHT - abs_position
+            '.'
         }
-    };
+    })
+}
+
+pub fn simulate_robot(robot: Robot, seconds: usize, board: &Board) -> Robot {
+    let (x, y) = robot.position;
+    let (velocity_x, velocity_y) = robot.velocity;
+    let seconds = seconds as i64;
 
     Robot {
-        position: (final_x_position, final_y_position),
+        position: (
+            wrap_add(x, velocity_x as i64 * seconds, board.width),
+            wrap_add(y, velocity_y as i64 * seconds, board.height),
+        ),
         velocity: robot.velocity,
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
+/// Multiplies the number of robots in each quadrant after `seconds`. Robots on the middle lines
+/// do not count.
+pub fn safety_factor(input: &str, board: &Board, seconds: usize) -> u32 {
     let robots = parse_input(input);
     let final_robots = robots
         .iter()
-        .map(|robot| simulate_robot(*robot, 100))
+        .map(|robot| simulate_robot(*robot, seconds, board))
         .collect::<Vec<Robot>>();
+    let (mid_x, mid_y) = (board.width / 2, board.height / 2);
     let q1_cnt = final_robots
         .iter()
-        .filter(|x| x.position.0 < MAZE_WIDTH / 2 && x.position.1 < MAZE_HEIGHT / 2)
+        .filter(|x| x.position.0 < mid_x && x.position.1 < mid_y)
         .count() as u32;
     let q2_cnt = final_robots
         .iter()
-        .filter(|x| x.position.0 > MAZE_WIDTH / 2 && x.position.1 < MAZE_HEIGHT / 2)
+        .filter(|x| x.position.0 > mid_x && x.position.1 < mid_y)
         .count() as u32;
     let q3_cnt = final_robots
         .iter()
-        .filter(|x| x.position.0 < MAZE_WIDTH / 2 && x.position.1 > MAZE_HEIGHT / 2)
+        .filter(|x| x.position.0 < mid_x && x.position.1 > mid_y)
         .count() as u32;
     let q4_cnt = final_robots
         .iter()
-        .filter(|x| x.position.0 > MAZE_WIDTH / 2 && x.position.1 > MAZE_HEIGHT / 2)
+        .filter(|x| x.position.0 > mid_x && x.position.1 > mid_y)
         .count() as u32;
-    Some(q1_cnt * q2_cnt * q3_cnt * q4_cnt)
+    q1_cnt * q2_cnt * q3_cnt * q4_cnt
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+/// Part one on any board, e.g. the smaller board of the example.
+pub fn part_one_on(input: &str, board: &Board) -> Option<u32> {
+    Some(safety_factor(input, board, 100))
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    part_one_on(input, &Board::PUZZLE)
+}
+
+/// Sum of squared deviations from the mean, scaled by `n²` to stay an integer. Robots that form a
+/// picture cluster together, which minimises their variance.
+fn spread(values: impl Iterator<Item = usize>) -> u64 {
+    let (n, sum, sum_of_squares) = values.fold((0, 0, 0), |(n, sum, squares), value| {
+        let value = value as u64;
+        (n + 1, sum + value, squares + value * value)
+    });
+    n * sum_of_squares - sum * sum
+}
+
+/// Finds the first second at which the robots form a picture.
+pub fn find_picture(input: &str, board: &Board) -> Option<u32> {
     let robots = parse_input(input);
-    for i in (12..6600).step_by(101) {
+
+    // x positions repeat every `board.width` seconds and y positions every `board.height` seconds,
+    // so the most clustered second can be searched per axis and combined with the CRT.
+    let clustered_at = |period: usize, axis: fn(&Robot) -> usize| {
+        (0..period).min_by_key(|&seconds| {
+            spread(
+                robots
+                    .iter()
+                    .map(|robot| axis(&simulate_robot(*robot, seconds, board))),
+            )
+        })
+    };
+
+    let x_seconds = clustered_at(board.width, |robot| robot.position.0)?;
+    let y_seconds = clustered_at(board.height, |robot| robot.position.1)?;
+
+    let (seconds, _) = crt(&[
+        (x_seconds as i64, board.width as i64),
+        (y_seconds as i64, board.height as i64),
+    ])?;
+
+    visualize::record(|| {
         let final_robots = robots
             .iter()
-            .map(|robot| simulate_robot(*robot, i))
+            .map(|robot| simulate_robot(*robot, seconds as usize, board))
             .collect::<Vec<Robot>>();
-        println!("iter {}", i);
-        render_map(final_robots);
+        render_map(&final_robots, board, format!("{seconds} seconds"))
+    });
+
+    Some(seconds as u32)
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    find_picture(input, &Board::PUZZLE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example uses a smaller board than the real input.
+    const EXAMPLE_BOARD: Board = Board {
+        width: 11,
+        height: 7,
+    };
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(part_one_on(&input, &EXAMPLE_BOARD), Some(12));
+    }
+
+    #[test]
+    fn test_simulate_robot() {
+        let robot = Robot {
+            position: (2, 4),
+            velocity: (2, -3),
+        };
+        let positions = (1..=5)
+            .map(|seconds| simulate_robot(robot, seconds, &EXAMPLE_BOARD).position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [(4, 1), (6, 5), (8, 2), (10, 6), (1, 3)]);
+    }
+
+    /// Generates robots of which half form a square picture after `seconds`.
+    fn picture_input(seconds: usize) -> String {
+        let mut rng = Rng::new(14);
+        (0..500)
+            .map(|idx| {
+                let position = if idx % 2 == 0 {
+                    (40 + rng.index(20), 40 + rng.index(20))
+                } else {
+                    (
+                        rng.index(Board::PUZZLE.width),
+                        rng.index(Board::PUZZLE.height),
+                    )
+                };
+                let velocity = (rng.range(-99..=99) as isize, rng.range(-99..=99) as isize);
+                // walk back from the picture to the start.
+                let start = simulate_robot(
+                    Robot {
+                        position,
+                        velocity: (-velocity.0, -velocity.1),
+                    },
+                    seconds,
+                    &Board::PUZZLE,
+                );
+                format!(
+                    "p={},{} v={},{}\n",
+                    start.position.0, start.position.1, velocity.0, velocity.1
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_part_two() {
+        assert_eq!(part_two(&picture_input(6577)), Some(6577));
+        assert_eq!(part_two(&picture_input(1234)), Some(1234));
     }
-    Some(6577)
 }
@@ -0,0 +1,168 @@
+//! Directed graphs over small integer nodes, e.g. ordering rules between page numbers.
+use std::error::Error;
+use std::fmt::Display;
+
+/// A directed graph over the nodes `0..len`, stored as an adjacency bitset so that checking for
+/// an edge is a single lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiGraph {
+    len: usize,
+    words_per_node: usize,
+    edges: Vec<u64>,
+}
+
+/// Returned when nodes cannot be ordered because their edges form a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    /// The nodes left unordered, each of them is on or behind a cycle.
+    pub nodes: Vec<usize>,
+}
+
+impl Error for CycleError {}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the edges between nodes {:?} form a cycle", self.nodes)
+    }
+}
+
+impl DiGraph {
+    pub fn new(len: usize) -> Self {
+        let words_per_node = len.div_ceil(64);
+        Self {
+            len,
+            words_per_node,
+            edges: vec![0; len * words_per_node],
+        }
+    }
+
+    /// Creates a graph with `len` nodes and the given `(from, to)` edges.
+    pub fn from_edges(len: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut graph = Self::new(len);
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn position(&self, from: usize, to: usize) -> (usize, u64) {
+        assert!(
+            from < self.len && to < self.len,
+            "edge ({from}, {to}) is outside of a graph with {} nodes",
+            self.len
+        );
+        (from * self.words_per_node + to / 64, 1 << (to % 64))
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        let (word, bit) = self.position(from, to);
+        self.edges[word] |= bit;
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        let (word, bit) = self.position(from, to);
+        self.edges[word] & bit != 0
+    }
+
+    /// The nodes that `node` has an edge to, in ascending order.
+    pub fn successors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(move |&to| self.has_edge(node, to))
+    }
+
+    /// Orders `nodes` so that every edge between two of them points forward, ignoring edges to
+    /// other nodes. Nodes without an edge between them keep their relative order.
+    ///
+    /// Only the edges between `nodes` have to be acyclic, the whole graph may contain cycles.
+    pub fn toposort(&self, nodes: &[usize]) -> Result<Vec<usize>, CycleError> {
+        // number of edges into every node, from the other nodes.
+        let mut in_degrees: Vec<usize> = nodes
+            .iter()
+            .map(|&to| {
+                nodes
+                    .iter()
+                    .filter(|&&from| self.has_edge(from, to))
+                    .count()
+            })
+            .collect();
+
+        let mut sorted = Vec::with_capacity(nodes.len());
+        let mut is_sorted = vec![false; nodes.len()];
+
+        while sorted.len() < nodes.len() {
+            let Some(next) = (0..nodes.len()).find(|&idx| !is_sorted[idx] && in_degrees[idx] == 0)
+            else {
+                let nodes = (0..nodes.len())
+                    .filter(|&idx| !is_sorted[idx])
+                    .map(|idx| nodes[idx])
+                    .collect();
+                return Err(CycleError { nodes });
+            };
+
+            is_sorted[next] = true;
+            sorted.push(nodes[next]);
+            for (idx, &to) in nodes.iter().enumerate() {
+                if self.has_edge(nodes[next], to) {
+                    in_degrees[idx] -= 1;
+                }
+            }
+        }
+
+        Ok(sorted)
+    }
+
+    /// Checks whether every edge between two of `nodes` points forward.
+    pub fn is_sorted(&self, nodes: &[usize]) -> bool {
+        nodes.iter().enumerate().all(|(idx, &to)| {
+            nodes[idx + 1..]
+                .iter()
+                .all(|&from| !self.has_edge(from, to))
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{CycleError, DiGraph};
+
+    #[test]
+    fn stores_edges() {
+        let graph = DiGraph::from_edges(130, [(1, 2), (2, 129), (129, 0)]);
+        assert!(graph.has_edge(2, 129));
+        assert!(!graph.has_edge(129, 2));
+        assert_eq!(graph.successors(129).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(graph.len(), 130);
+    }
+
+    #[test]
+    fn sorts_topologically() {
+        let graph = DiGraph::from_edges(5, [(3, 1), (1, 4), (3, 4)]);
+        assert_eq!(graph.toposort(&[4, 1, 3]), Ok(vec![3, 1, 4]));
+        // nodes without edges between them keep their order.
+        assert_eq!(graph.toposort(&[2, 4, 0, 1]), Ok(vec![2, 0, 1, 4]));
+        assert!(graph.is_sorted(&[3, 2, 1, 4]));
+        assert!(!graph.is_sorted(&[4, 3]));
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let graph = DiGraph::from_edges(4, [(0, 1), (1, 2), (2, 0), (2, 3)]);
+        assert_eq!(
+            graph.toposort(&[3, 2, 1, 0]),
+            Err(CycleError {
+                nodes: vec![3, 2, 1, 0]
+            })
+        );
+        // the cycle only matters if all of its nodes are sorted.
+        assert_eq!(graph.toposort(&[3, 2, 1]), Ok(vec![1, 2, 3]));
+    }
+}
@@ -1,3 +1,8 @@
+pub mod graph;
+pub mod math;
+pub mod memo;
+pub mod region;
 pub mod template;
+pub mod word_search;
 
 // Use this file to add helper functions and additional modules.
@@ -1,134 +1,94 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
-use args::{parse, AppArguments};
+use advent_of_code::template::args::{completions, AppArguments, Cli};
+use advent_of_code::template::commands::{all, download, read, scaffold, solve, stress, time};
+use advent_of_code::template::config::{Config, ConfigOverrides};
+use advent_of_code::template::DaySelection;
 
 #[cfg(feature = "today")]
 use advent_of_code::template::Day;
 #[cfg(feature = "today")]
 use std::process;
 
-mod args {
-    use advent_of_code::template::Day;
-    use std::process;
-
-    pub enum AppArguments {
-        Download {
-            day: Day,
-        },
-        Read {
-            day: Day,
-        },
-        Scaffold {
-            day: Day,
-            download: bool,
-            overwrite: bool,
-        },
-        Solve {
-            day: Day,
-            release: bool,
-            dhat: bool,
-            submit: Option<u8>,
-        },
-        All {
-            release: bool,
-        },
-        Time {
-            all: bool,
-            day: Option<Day>,
-            store: bool,
-        },
-        #[cfg(feature = "today")]
-        Today,
-    }
-
-    pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
-        let mut args = pico_args::Arguments::from_env();
-
-        let app_args = match args.subcommand()?.as_deref() {
-            Some("all") => AppArguments::All {
-                release: args.contains("--release"),
-            },
-            Some("time") => {
-                let all = args.contains("--all");
-                let store = args.contains("--store");
-
-                AppArguments::Time {
-                    all,
-                    day: args.opt_free_from_str()?,
-                    store,
-                }
-            }
-            Some("download") => AppArguments::Download {
-                day: args.free_from_str()?,
-            },
-            Some("read") => AppArguments::Read {
-                day: args.free_from_str()?,
-            },
-            Some("scaffold") => AppArguments::Scaffold {
-                day: args.free_from_str()?,
-                download: args.contains("--download"),
-                overwrite: args.contains("--overwrite"),
-            },
-            Some("solve") => AppArguments::Solve {
-                day: args.free_from_str()?,
-                release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
-                dhat: args.contains("--dhat"),
-            },
-            #[cfg(feature = "today")]
-            Some("today") => AppArguments::Today,
-            Some(x) => {
-                eprintln!("Unknown command: {x}");
-                process::exit(1);
-            }
-            None => {
-                eprintln!("No command specified.");
-                process::exit(1);
-            }
-        };
-
-        let remaining = args.finish();
-        if !remaining.is_empty() {
-            eprintln!("Warning: unknown argument(s): {remaining:?}.");
-        }
-
-        Ok(app_args)
-    }
+fn load_config(overrides: &ConfigOverrides) -> Config {
+    Config::load_or_exit().with_overrides(overrides)
 }
 
 fn main() {
-    match parse() {
+    match Cli::from_env() {
         Err(err) => {
             eprintln!("Error: {err}");
             std::process::exit(1);
         }
-        Ok(args) => match args {
-            AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
-            AppArguments::Download { day } => download::handle(day),
-            AppArguments::Read { day } => read::handle(day),
+        Ok(Cli { command, overrides }) => match command {
+            AppArguments::Help(text) => println!("{text}"),
+            AppArguments::Completions { shell } => println!("{}", completions(shell)),
+            AppArguments::All { days, release } => all::handle(
+                &days.unwrap_or_else(DaySelection::all),
+                release,
+                &load_config(&overrides),
+                &overrides,
+            ),
+            AppArguments::Time {
+                history: Some(day), ..
+            } => time::history(day, &load_config(&overrides)),
+            AppArguments::Time {
+                days,
+                all,
+                store,
+                sinks,
+                history: None,
+            } => {
+                time::handle(
+                    days,
+                    all,
+                    store,
+                    sinks.as_deref(),
+                    &load_config(&overrides),
+                    &overrides,
+                );
+            }
+            AppArguments::Download { day } => download::handle(day, &load_config(&overrides)),
+            AppArguments::Read { day } => read::handle(day, &load_config(&overrides)),
             AppArguments::Scaffold {
                 day,
                 download,
                 overwrite,
             } => {
-                scaffold::handle(day, overwrite);
+                let config = load_config(&overrides);
+                scaffold::handle(day, overwrite, &config);
                 if download {
-                    download::handle(day);
+                    download::handle(day, &config);
                 }
             }
             AppArguments::Solve {
-                day,
+                days,
+                release,
+                dhat,
+                store,
+                variants,
+                visualize,
+                submit,
+                options,
+            } => solve::handle(
+                &days,
                 release,
                 dhat,
+                store,
+                variants,
+                visualize,
                 submit,
-            } => solve::handle(day, release, dhat, submit),
+                &options,
+                &load_config(&overrides),
+                &overrides,
+            ),
+            AppArguments::Stress { day, size, seed } => stress::handle(day, size, seed, &overrides),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
                     Some(day) => {
-                        scaffold::handle(day, false);
-                        download::handle(day);
-                        read::handle(day)
+                        let config = load_config(&overrides);
+                        scaffold::handle(day, false, &config);
+                        download::handle(day, &config);
+                        read::handle(day, &config)
                     }
                     None => {
                         eprintln!(
@@ -0,0 +1,383 @@
+//! Number theory, linear algebra and digit helpers shared by solutions.
+use std::fmt::Display;
+
+/// Returns `(g, x, y)` such that `a * x + b * y = g`, where `g` is the greatest common divisor of
+/// `a` and `b`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Returns the greatest common divisor of `a` and `b`, which is never negative.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    extended_gcd(a, b).0
+}
+
+/// Returns the least common multiple of `a` and `b`, or `None` if it overflows.
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b).map(i64::abs)
+}
+
+/// Returns the inverse of `a` modulo `m`, or `None` if `a` and `m` are not coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Solves a system of congruences `x ≡ remainder (mod modulus)` with the Chinese Remainder Theorem.
+///
+/// Returns the smallest non-negative solution together with the modulus it repeats with (the lcm
+/// of all moduli), or `None` if the congruences contradict each other. The moduli do not have to
+/// be coprime.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences
+        .iter()
+        .try_fold((0_i64, 1_i64), |(x, lcm), &(remainder, modulus)| {
+            let (g, _, _) = extended_gcd(lcm, modulus);
+            let difference = remainder - x;
+            if difference % g != 0 {
+                return None;
+            }
+
+            // solve `x + lcm * k ≡ remainder (mod modulus)` for `k`.
+            let reduced = modulus / g;
+            let k = i128::from(difference / g) * i128::from(mod_inverse(lcm / g, reduced)?)
+                % i128::from(reduced);
+            let next_lcm = i128::from(lcm) * i128::from(reduced);
+            let next_x = (i128::from(x) + i128::from(lcm) * k).rem_euclid(next_lcm);
+
+            Some((i64::try_from(next_x).ok()?, i64::try_from(next_lcm).ok()?))
+        })
+}
+
+/// Moves `position` by `delta` on a ring of `size` cells, wrapping around at both ends.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn wrap_add(position: usize, delta: i64, size: usize) -> usize {
+    (position as i128 + i128::from(delta)).rem_euclid(size as i128) as usize
+}
+
+/// Divides `a` by `b` if the result is an integer.
+pub fn div_exact(a: i64, b: i64) -> Option<i64> {
+    (b != 0 && a % b == 0).then(|| a / b)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Returns the number of decimal digits of `n`, `0` has one digit.
+pub fn digit_count(n: u64) -> u32 {
+    n.checked_ilog10().map_or(1, |log| log + 1)
+}
+
+/// Returns `10^exp`, or `None` if it overflows.
+pub fn checked_pow10(exp: u32) -> Option<u64> {
+    10_u64.checked_pow(exp)
+}
+
+/// Splits off the last `digits` decimal digits of `n`, e.g. `split_digits(253_000, 3) == (253, 0)`.
+pub fn split_digits(n: u64, digits: u32) -> (u64, u64) {
+    match checked_pow10(digits) {
+        Some(divisor) => (n / divisor, n % divisor),
+        None => (0, n),
+    }
+}
+
+/// Appends the decimal digits of `b` to `a`, e.g. `checked_concat(12, 345) == Some(12_345)`.
+/// Returns `None` if the result overflows.
+pub fn checked_concat(a: u64, b: u64) -> Option<u64> {
+    a.checked_mul(checked_pow10(digit_count(b))?)?
+        .checked_add(b)
+}
+
+/// Removes the decimal digits of `suffix` from the end of `n`, the inverse of [`checked_concat`],
+/// e.g. `strip_digits(12_345, 345) == Some(12)`. Returns `None` if `n` does not end with `suffix`.
+pub fn strip_digits(n: u64, suffix: u64) -> Option<u64> {
+    let (rest, tail) = split_digits(n, digit_count(suffix));
+    (tail == suffix && n >= suffix).then_some(rest)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An exact fraction. It is always reduced and its denominator is positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    numer: i128,
+    denom: i128,
+}
+
+impl Ratio {
+    /// Creates the fraction `numer / denom`, or `None` if `denom` is zero.
+    pub fn new(numer: i128, denom: i128) -> Option<Self> {
+        if denom == 0 {
+            return None;
+        }
+        let divisor = gcd_i128(numer, denom) * denom.signum();
+        Some(Self {
+            numer: numer / divisor,
+            denom: denom / divisor,
+        })
+    }
+
+    pub fn integer(n: i64) -> Self {
+        Self {
+            numer: i128::from(n),
+            denom: 1,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    /// Returns the value if it is an integer that fits into an `i64`.
+    pub fn to_integer(&self) -> Option<i64> {
+        if self.is_integer() {
+            i64::try_from(self.numer).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Self::new(
+            self.numer
+                .checked_mul(other.denom)?
+                .checked_add(other.numer.checked_mul(self.denom)?)?,
+            self.denom.checked_mul(other.denom)?,
+        )
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(Self {
+            numer: other.numer.checked_neg()?,
+            denom: other.denom,
+        })
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        Self::new(
+            self.numer.checked_mul(other.numer)?,
+            self.denom.checked_mul(other.denom)?,
+        )
+    }
+
+    /// Returns `None` when dividing by zero or on overflow.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        Self::new(
+            self.numer.checked_mul(other.denom)?,
+            self.denom.checked_mul(other.numer)?,
+        )
+    }
+}
+
+impl From<i64> for Ratio {
+    fn from(n: i64) -> Self {
+        Self::integer(n)
+    }
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+fn gcd_i128(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+/// Solves `matrix * [x, y] = rhs` exactly with Cramer's rule.
+/// Returns `None` if the system has no unique solution.
+pub fn solve_2x2(matrix: [[i64; 2]; 2], rhs: [i64; 2]) -> Option<[Ratio; 2]> {
+    let [[a, b], [c, d]] = matrix.map(|row| row.map(i128::from));
+    let [e, f] = rhs.map(i128::from);
+
+    let determinant = a * d - b * c;
+    if determinant == 0 {
+        return None;
+    }
+
+    Some([
+        Ratio::new(e * d - b * f, determinant)?,
+        Ratio::new(a * f - e * c, determinant)?,
+    ])
+}
+
+/// Solves `matrix * x = rhs` exactly with Gaussian elimination over fractions.
+/// Returns `None` if the system has no unique solution or an intermediate value overflows.
+pub fn solve_linear<const N: usize>(matrix: [[i64; N]; N], rhs: [i64; N]) -> Option<[Ratio; N]> {
+    // augmented matrix `[matrix | rhs]`.
+    let mut rows: Vec<Vec<Ratio>> = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, value)| {
+            row.iter()
+                .chain([&value])
+                .map(|&x| Ratio::integer(x))
+                .collect()
+        })
+        .collect();
+
+    for col in 0..N {
+        let pivot = (col..N).find(|&row| rows[row][col].numer() != 0)?;
+        rows.swap(col, pivot);
+
+        let pivot_row = rows[col].clone();
+        for (idx, row) in rows.iter_mut().enumerate() {
+            if idx == col || row[col].numer() == 0 {
+                continue;
+            }
+            let factor = row[col].checked_div(pivot_row[col])?;
+            for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *value = value.checked_sub(factor.checked_mul(*pivot_value)?)?;
+            }
+        }
+    }
+
+    let mut solution = [Ratio::integer(0); N];
+    for (idx, value) in solution.iter_mut().enumerate() {
+        *value = rows[idx][N].checked_div(rows[idx][idx])?;
+    }
+    Some(solution)
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{
+        checked_concat, crt, digit_count, div_exact, extended_gcd, gcd, lcm, mod_inverse,
+        solve_2x2, solve_linear, split_digits, strip_digits, wrap_add, Ratio,
+    };
+
+    #[test]
+    fn computes_extended_gcd() {
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(extended_gcd(0, 5), (5, 0, 1));
+        assert_eq!(extended_gcd(-4, 6).0, 2);
+    }
+
+    #[test]
+    fn computes_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(101, 103), Some(51));
+        assert_eq!(mod_inverse(4, 6), None);
+    }
+
+    #[test]
+    fn solves_congruences() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(
+            crt(&[(6577 % 101, 101), (6577 % 103, 103)]),
+            Some((6577, 10403))
+        );
+        // non-coprime moduli
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn wraps_positions() {
+        assert_eq!(wrap_add(2, 3, 11), 5);
+        assert_eq!(wrap_add(2, -3, 11), 10);
+        assert_eq!(wrap_add(0, -300, 7), 1);
+        assert_eq!(wrap_add(6, i64::MAX, 7), (6 + i64::MAX % 7) as usize % 7);
+    }
+
+    #[test]
+    fn computes_gcd_and_lcm() {
+        assert_eq!(gcd(12, -18), 6);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(101, 103), Some(10403));
+        assert_eq!(lcm(i64::MAX, 2), None);
+        assert_eq!(div_exact(12, 4), Some(3));
+        assert_eq!(div_exact(12, 5), None);
+        assert_eq!(div_exact(12, 0), None);
+    }
+
+    #[test]
+    fn handles_digits() {
+        assert_eq!(digit_count(0), 1);
+        assert_eq!(digit_count(9), 1);
+        assert_eq!(digit_count(10), 2);
+        assert_eq!(digit_count(u64::MAX), 20);
+        assert_eq!(split_digits(253_000, 3), (253, 0));
+        assert_eq!(split_digits(1234, 2), (12, 34));
+        assert_eq!(checked_concat(12, 345), Some(12_345));
+        assert_eq!(checked_concat(6, 0), Some(60));
+        assert_eq!(checked_concat(u64::MAX, 1), None);
+        assert_eq!(strip_digits(12_345, 345), Some(12));
+        assert_eq!(strip_digits(345, 345), Some(0));
+        assert_eq!(strip_digits(12_345, 45_345), None);
+        assert_eq!(strip_digits(12_345, 44), None);
+    }
+
+    #[test]
+    fn computes_with_ratios() {
+        let half = Ratio::new(2, 4).unwrap();
+        assert_eq!((half.numer(), half.denom()), (1, 2));
+        assert_eq!(Ratio::new(3, -6), Ratio::new(-1, 2));
+        assert_eq!(Ratio::new(1, 0), None);
+        let third = Ratio::new(1, 3).unwrap();
+        assert_eq!(half.checked_add(third), Ratio::new(5, 6));
+        assert_eq!(half.checked_sub(third), Ratio::new(1, 6));
+        assert_eq!(half.checked_mul(third), Ratio::new(1, 6));
+        assert_eq!(half.checked_div(Ratio::integer(0)), None);
+        assert_eq!(Ratio::new(-6, 3).unwrap().to_integer(), Some(-2));
+        assert_eq!(half.to_string(), "1/2");
+    }
+
+    #[test]
+    fn solves_linear_systems() {
+        // day 13 example: 94a + 22b = 8400, 34a + 67b = 5400
+        let [a, b] = solve_2x2([[94, 22], [34, 67]], [8400, 5400]).unwrap();
+        assert_eq!((a.to_integer(), b.to_integer()), (Some(80), Some(40)));
+        assert_eq!(solve_2x2([[1, 2], [2, 4]], [3, 6]), None);
+
+        let [x, y] = solve_2x2([[2, 0], [0, 3]], [1, 1]).unwrap();
+        assert_eq!((x.to_string(), y.to_string()), ("1/2".into(), "1/3".into()));
+        assert_eq!(solve_linear([[2, 0], [0, 3]], [1, 1]), Some([x, y]));
+
+        // pivoting is required, the first coefficient is zero.
+        let solution = solve_linear([[0, 1, 1], [1, 0, 1], [1, 1, 0]], [5, 4, 3]).unwrap();
+        assert_eq!(
+            solution.map(|x| x.to_integer()),
+            [Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(solve_linear([[1, 1], [1, 1]], [1, 2]), None);
+    }
+}
@@ -0,0 +1,347 @@
+//! Memoisation tables for recursive solutions. The scope of a table is where it lives:
+//!  - per call: a local [`Memo`] (or [`ShardedMemo`] when shared with rayon) passed down the recursion.
+//!  - per part: a `static` [`ShardedMemo`] with [`Scope::Part`], emptied before every run of a part.
+//!  - global: a `static` [`ShardedMemo`] with [`Scope::Global`], kept for the whole process.
+//!
+//! Computations run without holding a lock, so they may recurse into the same table.
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher, Hash};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Number of independently locked shards of a [`ShardedMemo`].
+const SHARDS: usize = 64;
+
+/// Incremented before every run of a part, see [`start_part`].
+static PART_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+type Hasher = BuildHasherDefault<DefaultHasher>;
+
+/// Marks the start of a part run. Tables with [`Scope::Part`] drop their entries and reset their
+/// stats on next access, so that benchmark samples don't measure the cache of the previous sample.
+pub(crate) fn start_part() {
+    PART_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// How long the entries of a `static` [`ShardedMemo`] live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Entries and stats are dropped before every run of a part.
+    Part,
+    /// Entries are kept as long as the table lives, until the process exits for a `static`.
+    Global,
+}
+
+/// Hit and miss counts of a memoisation table since it was created, or since the start of the
+/// current part for [`Scope::Part`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+impl MemoStats {
+    /// Share of lookups that were answered from the table, between 0 and 1.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = self.hits as f64 / lookups as f64;
+        rate
+    }
+}
+
+impl Display for MemoStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} entries",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.entries
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A single-threaded memoisation table.
+#[derive(Debug)]
+pub struct Memo<K, V> {
+    map: RefCell<HashMap<K, V>>,
+    hits: Cell<u64>,
+    misses: Cell<u64>,
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            map: RefCell::new(HashMap::new()),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+        }
+    }
+
+    /// Returns the value stored for `key`, or computes and stores it with `compute`.
+    pub fn get_or_insert_with(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.map.borrow().get(&key) {
+            self.hits.set(self.hits.get() + 1);
+            return value.clone();
+        }
+        self.misses.set(self.misses.get() + 1);
+
+        let value = compute();
+        self.map.borrow_mut().insert(key, value.clone());
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.map.borrow().get(key).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.map.borrow_mut().clear();
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            entries: self.len(),
+        }
+    }
+}
+
+/// A table with [`Scope::Global`], e.g. to share between the rayon threads of a single call.
+impl<K: Hash + Eq, V: Clone> Default for ShardedMemo<K, V> {
+    fn default() -> Self {
+        Self::new(Scope::Global)
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[derive(Debug)]
+struct Shard<K, V> {
+    generation: u64,
+    map: HashMap<K, V, Hasher>,
+}
+
+/// A thread-safe memoisation table, split into shards with their own lock so that parallel
+/// lookups rarely wait for each other. Can be used in a `static`.
+///
+/// Two threads missing the same key at once both compute it; the value stored last wins.
+#[derive(Debug)]
+pub struct ShardedMemo<K, V> {
+    scope: Scope,
+    shards: [Mutex<Shard<K, V>>; SHARDS],
+    /// Part generation the hit and miss counts belong to.
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Hash + Eq, V: Clone> ShardedMemo<K, V> {
+    pub const fn new(scope: Scope) -> Self {
+        Self {
+            scope,
+            shards: [const {
+                Mutex::new(Shard {
+                    generation: 0,
+                    map: HashMap::with_hasher(BuildHasherDefault::new()),
+                })
+            }; SHARDS],
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The generation entries and stats have to belong to, `None` if they never go stale.
+    fn current_generation(&self) -> Option<u64> {
+        (self.scope == Scope::Part).then(|| PART_GENERATION.load(Ordering::Relaxed))
+    }
+
+    /// Locks the shard with index `idx`, dropping its stale entries first.
+    fn lock_shard(&self, idx: usize) -> MutexGuard<'_, Shard<K, V>> {
+        let mut shard = self.shards[idx].lock().unwrap();
+
+        if let Some(generation) = self.current_generation() {
+            if shard.generation != generation {
+                shard.map.clear();
+                shard.generation = generation;
+            }
+        }
+
+        shard
+    }
+
+    /// Locks the shard of `key`, dropping its stale entries first.
+    fn shard(&self, key: &K) -> MutexGuard<'_, Shard<K, V>> {
+        #[allow(clippy::cast_possible_truncation)]
+        let idx = Hasher::default().hash_one(key) as usize % SHARDS;
+        self.lock_shard(idx)
+    }
+
+    /// Resets the hit and miss counts if they belong to a previous part.
+    fn sync_stats(&self) {
+        if let Some(generation) = self.current_generation() {
+            if self.generation.swap(generation, Ordering::Relaxed) != generation {
+                self.hits.store(0, Ordering::Relaxed);
+                self.misses.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns the value stored for `key`, or computes and stores it with `compute`.
+    pub fn get_or_insert_with(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        self.sync_stats();
+        if let Some(value) = self.get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return value;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let value = compute();
+        self.shard(&key).map.insert(key, value.clone());
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.shard(key).map.get(key).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        (0..SHARDS).map(|idx| self.lock_shard(idx).map.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().unwrap().map.clear();
+        }
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.sync_stats();
+        MemoStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.len(),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{start_part, Memo, MemoStats, Scope, ShardedMemo, PART_GENERATION};
+    use std::sync::atomic::Ordering;
+
+    fn fib(memo: &Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, || fib(memo, n - 1) + fib(memo, n - 2))
+    }
+
+    #[test]
+    fn memoizes_recursion() {
+        let memo = Memo::new();
+        assert_eq!(fib(&memo, 90), 2_880_067_194_370_816_120);
+        assert_eq!(memo.len(), 89);
+        assert_eq!(memo.stats().misses, 89);
+        assert_eq!(memo.stats().hits, 87);
+    }
+
+    #[test]
+    fn shares_between_threads() {
+        static SQUARES: ShardedMemo<u64, u64> = ShardedMemo::new(Scope::Global);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for n in 0..100 {
+                        assert_eq!(SQUARES.get_or_insert_with(n, || n * n), n * n);
+                    }
+                });
+            }
+        });
+
+        let stats = SQUARES.stats();
+        assert_eq!(stats.entries, 100);
+        assert_eq!(stats.hits + stats.misses, 400);
+        assert_eq!(SQUARES.get(&12), Some(144));
+    }
+
+    #[test]
+    fn drops_part_scoped_entries() {
+        static PART: ShardedMemo<u8, u8> = ShardedMemo::new(Scope::Part);
+
+        PART.get_or_insert_with(1, || 1);
+        assert_eq!(PART.get(&1), Some(1));
+        start_part();
+        assert_eq!(PART.get(&1), None);
+    }
+
+    #[test]
+    fn resets_stats_between_parts() {
+        static PART: ShardedMemo<u8, u8> = ShardedMemo::new(Scope::Part);
+
+        // other tests may start a part at any time, so each part retries until it ran undisturbed.
+        let run_part = |keys: &[u8]| loop {
+            start_part();
+            let generation = PART_GENERATION.load(Ordering::Relaxed);
+            for &key in keys {
+                PART.get_or_insert_with(key, || key);
+            }
+            let stats = PART.stats();
+            if PART_GENERATION.load(Ordering::Relaxed) == generation {
+                break stats;
+            }
+        };
+
+        let first = run_part(&[1, 2, 1, 1]);
+        assert_eq!((first.hits, first.misses, first.entries), (2, 2, 2));
+
+        let second = run_part(&[3]);
+        assert_eq!((second.hits, second.misses, second.entries), (0, 1, 1));
+    }
+
+    #[test]
+    fn formats_stats() {
+        let stats = MemoStats {
+            hits: 3,
+            misses: 1,
+            entries: 1,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "3 hits, 1 misses (75.0% hit rate), 1 entries"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Connected regions of equal cells in a grid, e.g. garden plots of the same plant.
+
+/// A set of horizontally or vertically connected cells with the same value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Index of the region in [`RegionMap::regions`], also used as the label of its cells.
+    pub label: usize,
+    /// Positions `(x, y)` of the cells, in the order the region was filled.
+    pub cells: Vec<(usize, usize)>,
+    /// Top left corner of the bounding box.
+    pub min: (usize, usize),
+    /// Bottom right corner of the bounding box, inclusive.
+    pub max: (usize, usize),
+    /// Number of cell edges that border another region or the outside of the grid.
+    pub perimeter: usize,
+    /// Number of straight fences around the region, including the ones around holes.
+    pub sides: usize,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Width and height of the bounding box.
+    pub fn size(&self) -> (usize, usize) {
+        (self.max.0 - self.min.0 + 1, self.max.1 - self.min.1 + 1)
+    }
+}
+
+/// A grid with every cell labelled with its region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionMap {
+    width: usize,
+    height: usize,
+    labels: Vec<usize>,
+    regions: Vec<Region>,
+}
+
+/// Offsets of the four neighbours of a cell, in clockwise order.
+const NEIGHBOURS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+impl RegionMap {
+    /// Labels the regions of `grid` and measures them. Rows are expected to have the same length.
+    pub fn new<T: PartialEq>(grid: &[Vec<T>]) -> Self {
+        let height = grid.len();
+        let width = grid.first().map_or(0, Vec::len);
+        let mut map = RegionMap {
+            width,
+            height,
+            labels: vec![usize::MAX; width * height],
+            regions: vec![],
+        };
+
+        // label the regions with an iterative flood fill.
+        let mut stack = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                if map.labels[y * width + x] != usize::MAX {
+                    continue;
+                }
+
+                let mut region = Region {
+                    label: map.regions.len(),
+                    cells: vec![],
+                    min: (x, y),
+                    max: (x, y),
+                    perimeter: 0,
+                    sides: 0,
+                };
+                map.labels[y * width + x] = region.label;
+                stack.push((x, y));
+
+                while let Some((cx, cy)) = stack.pop() {
+                    region.cells.push((cx, cy));
+                    region.min = (region.min.0.min(cx), region.min.1.min(cy));
+                    region.max = (region.max.0.max(cx), region.max.1.max(cy));
+
+                    for offset in NEIGHBOURS {
+                        let Some((nx, ny)) = map.offset((cx, cy), offset) else {
+                            continue;
+                        };
+                        let idx = ny * width + nx;
+                        if map.labels[idx] == usize::MAX && grid[ny][nx] == grid[cy][cx] {
+                            map.labels[idx] = region.label;
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+
+                map.regions.push(region);
+            }
+        }
+
+        // measure all regions in one sweep over the labelled grid.
+        for y in 0..height {
+            for x in 0..width {
+                let label = map.labels[y * width + x];
+                let same = |offset| {
+                    map.offset((x, y), offset)
+                        .is_some_and(|(nx, ny)| map.labels[ny * width + nx] == label)
+                };
+
+                let mut perimeter = 0;
+                let mut corners = 0;
+                for (idx, offset) in NEIGHBOURS.iter().enumerate() {
+                    let next = NEIGHBOURS[(idx + 1) % 4];
+                    let (side, next_side) = (same(*offset), same(next));
+                    if !side {
+                        perimeter += 1;
+                    }
+                    // every corner of the outline starts a new side.
+                    let diagonal = (offset.0 + next.0, offset.1 + next.1);
+                    if (!side && !next_side) || (side && next_side && !same(diagonal)) {
+                        corners += 1;
+                    }
+                }
+
+                let region = &mut map.regions[label];
+                region.perimeter += perimeter;
+                region.sides += corners;
+            }
+        }
+
+        map
+    }
+
+    /// The neighbour of `position` at `offset`, or `None` outside of the grid.
+    fn offset(&self, (x, y): (usize, usize), (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(dx).filter(|&x| x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|&y| y < self.height)?;
+        Some((x, y))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The label of the region of the cell at `(x, y)`.
+    pub fn label(&self, (x, y): (usize, usize)) -> usize {
+        self.labels[y * self.width + x]
+    }
+
+    /// The region of the cell at `(x, y)`.
+    pub fn region_at(&self, position: (usize, usize)) -> &Region {
+        &self.regions[self.label(position)]
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::RegionMap;
+
+    fn grid(s: &str) -> Vec<Vec<char>> {
+        s.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    #[test]
+    fn labels_regions() {
+        let map = RegionMap::new(&grid("RRRRI\nRRRRI\nVVRRR\nVVRCC"));
+        assert_eq!(map.regions().len(), 4);
+        assert_eq!(map.label((0, 0)), map.label((4, 2)));
+        assert_ne!(map.label((4, 0)), map.label((4, 2)));
+
+        let region = map.region_at((0, 0));
+        assert_eq!(region.area(), 12);
+        assert_eq!(region.perimeter, 18);
+        assert_eq!(region.sides, 10);
+        assert_eq!((region.min, region.max), ((0, 0), (4, 3)));
+        assert_eq!(map.region_at((3, 3)).size(), (2, 1));
+    }
+
+    #[test]
+    fn counts_sides_of_holes() {
+        let map = RegionMap::new(&grid("AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA"));
+        let outer = map.region_at((0, 0));
+        assert_eq!(outer.area(), 28);
+        assert_eq!(outer.sides, 12);
+        // the two B regions only touch diagonally.
+        assert_ne!(map.label((3, 1)), map.label((2, 3)));
+        assert_eq!(map.region_at((3, 1)).sides, 4);
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     process::{Command, Output, Stdio},
 };
 
+use crate::template::config::Config;
 use crate::template::Day;
 
 #[derive(Debug)]
@@ -33,8 +34,8 @@ pub fn check() -> Result<(), AocCommandError> {
     Ok(())
 }
 
-pub fn read(day: Day) -> Result<Output, AocCommandError> {
-    let puzzle_path = get_puzzle_path(day);
+pub fn read(day: Day, config: &Config) -> Result<Output, AocCommandError> {
+    let puzzle_path = get_puzzle_path(day, config);
 
     let args = build_args(
         "read",
@@ -44,14 +45,15 @@ pub fn read(day: Day) -> Result<Output, AocCommandError> {
             puzzle_path,
         ],
         day,
+        config,
     );
 
     call_aoc_cli(&args)
 }
 
-pub fn download(day: Day) -> Result<Output, AocCommandError> {
-    let input_path = get_input_path(day);
-    let puzzle_path = get_puzzle_path(day);
+pub fn download(day: Day, config: &Config) -> Result<Output, AocCommandError> {
+    let input_path = get_input_path(day, config);
+    let puzzle_path = get_puzzle_path(day, config);
 
     let args = build_args(
         "download",
@@ -63,6 +65,7 @@ pub fn download(day: Day) -> Result<Output, AocCommandError> {
             puzzle_path.to_string(),
         ],
         day,
+        config,
     );
 
     let output = call_aoc_cli(&args)?;
@@ -72,47 +75,71 @@ pub fn download(day: Day) -> Result<Output, AocCommandError> {
     Ok(output)
 }
 
-pub fn submit(day: Day, part: u8, result: &str) -> Result<Output, AocCommandError> {
+pub fn submit(
+    day: Day,
+    part: u8,
+    result: &str,
+    config: &Config,
+) -> Result<Output, AocCommandError> {
     // workaround: the argument order is inverted for submit.
-    let mut args = build_args("submit", &[], day);
+    let mut args = build_args("submit", &[], day, config);
     args.push(part.to_string());
     args.push(result.to_string());
-    call_aoc_cli(&args)
+
+    // the output is captured to check whether the answer was accepted.
+    let output = call_aoc_cli_with(&args, Stdio::piped());
+    if let Ok(output) | Err(AocCommandError::BadExitStatus(output)) = &output {
+        print!("{}", String::from_utf8_lossy(&output.stdout));
+    }
+    output
 }
 
-fn get_input_path(day: Day) -> String {
-    format!("data/inputs/{day}.txt")
+/// Checks whether the output of [`submit`] reports a correct answer.
+pub fn is_correct_answer(output: &Output) -> bool {
+    String::from_utf8_lossy(&output.stdout).contains("That's the right answer")
 }
 
-fn get_puzzle_path(day: Day) -> String {
-    format!("data/puzzles/{day}.md")
+fn get_input_path(day: Day, config: &Config) -> String {
+    config
+        .data_file("inputs", day, "txt")
+        .to_string_lossy()
+        .to_string()
 }
 
-fn get_year() -> Option<u16> {
-    match std::env::var("AOC_YEAR") {
-        Ok(x) => x.parse().ok().or(None),
-        Err(_) => None,
-    }
+fn get_puzzle_path(day: Day, config: &Config) -> String {
+    config
+        .data_file("puzzles", day, "md")
+        .to_string_lossy()
+        .to_string()
 }
 
-fn build_args(command: &str, args: &[String], day: Day) -> Vec<String> {
+fn build_args(command: &str, args: &[String], day: Day, config: &Config) -> Vec<String> {
     let mut cmd_args = args.to_vec();
 
-    if let Some(year) = get_year() {
+    if let Some(year) = config.year {
         cmd_args.push("--year".into());
         cmd_args.push(year.to_string());
     }
 
+    if let Some(session_file) = &config.session_file {
+        cmd_args.push("--session-file".into());
+        cmd_args.push(session_file.to_string_lossy().to_string());
+    }
+
     cmd_args.append(&mut vec!["--day".into(), day.to_string(), command.into()]);
 
     cmd_args
 }
 
 fn call_aoc_cli(args: &[String]) -> Result<Output, AocCommandError> {
+    call_aoc_cli_with(args, Stdio::inherit())
+}
+
+fn call_aoc_cli_with(args: &[String], stdout: Stdio) -> Result<Output, AocCommandError> {
     // println!("Calling >aoc with: {}", args.join(" "));
     let output = Command::new("aoc")
         .args(args)
-        .stdout(Stdio::inherit())
+        .stdout(stdout)
         .stderr(Stdio::inherit())
         .output()
         .map_err(|_| AocCommandError::CommandNotCallable)?;
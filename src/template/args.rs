@@ -0,0 +1,1233 @@
+/// Typed command-line arguments shared by the launcher (`src/main.rs`) and the solution binaries.
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::template::benchmark_report::{parse_list, Sink};
+use crate::template::config::ConfigOverrides;
+use crate::template::options::{self, SolutionOption};
+use crate::template::visualize::Visualization;
+use crate::template::{Day, DaySelection};
+
+/// Parsed launcher invocation: the command and the config overrides that apply to it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cli {
+    pub command: AppArguments,
+    pub overrides: ConfigOverrides,
+}
+
+/// Arguments accepted by the launcher binary.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AppArguments {
+    Download {
+        day: Day,
+    },
+    Read {
+        day: Day,
+    },
+    Scaffold {
+        day: Day,
+        download: bool,
+        overwrite: bool,
+    },
+    Solve {
+        days: DaySelection,
+        release: bool,
+        dhat: bool,
+        store: bool,
+        variants: bool,
+        visualize: Option<Visualization>,
+        submit: Option<Part>,
+        options: Vec<SolutionOption>,
+    },
+    All {
+        days: Option<DaySelection>,
+        release: bool,
+    },
+    Time {
+        all: bool,
+        days: Option<DaySelection>,
+        store: bool,
+        sinks: Option<Vec<Sink>>,
+        history: Option<Day>,
+    },
+    Stress {
+        day: Day,
+        size: Scale,
+        seed: Option<u64>,
+    },
+    Completions {
+        shell: Shell,
+    },
+    /// Help was requested, the contained text should be printed as-is.
+    Help(String),
+    #[cfg(feature = "today")]
+    Today,
+}
+
+/// Arguments accepted by a solution binary, usually mirrored from `cargo solve` / `cargo time`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SolutionArguments {
+    /// Bench each part instead of running it once.
+    pub time: bool,
+    /// Submit the result of the given part via aoc-cli.
+    pub submit: Option<Part>,
+    /// Run and compare the registered variants of each part.
+    pub variants: bool,
+    /// Bench each part on generated inputs up to the given size.
+    pub stress: Option<Scale>,
+    /// Seed of the input generator used by `--stress`.
+    pub seed: Option<u64>,
+    /// Record the frames of grid simulations and show or export them.
+    pub visualize: Option<Visualization>,
+    /// Puzzle-specific options, read by the solution via [`crate::template::options`].
+    pub options: Vec<SolutionOption>,
+    /// Config values overridden on the command-line.
+    pub overrides: ConfigOverrides,
+}
+
+/// A puzzle part (1 or 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Part(u8);
+
+impl Part {
+    /// Creates a [`Part`] if the value is 1 or 2, returns [`None`] otherwise.
+    pub fn new(part: u8) -> Option<Self> {
+        (part == 1 || part == 2).then_some(Self(part))
+    }
+
+    /// Converts the [`Part`] into an [`u8`].
+    pub fn into_inner(self) -> u8 {
+        self.0
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .ok()
+            .and_then(Self::new)
+            .ok_or_else(|| "expecting part 1 or 2".to_string())
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The size of a generated input relative to the real puzzle input, e.g. `10x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scale(u32);
+
+impl Scale {
+    /// Creates a [`Scale`] if the value is at least 1, returns [`None`] otherwise.
+    pub fn new(scale: u32) -> Option<Self> {
+        (scale >= 1).then_some(Self(scale))
+    }
+
+    /// Converts the [`Scale`] into an [`u32`].
+    pub fn into_inner(self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self(8)
+    }
+}
+
+impl FromStr for Scale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_suffix('x')
+            .unwrap_or(s)
+            .parse()
+            .ok()
+            .and_then(Self::new)
+            .ok_or_else(|| "expecting a size like `10x`".to_string())
+    }
+}
+
+impl Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x", self.0)
+    }
+}
+
+/// A shell that completion scripts can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err("expecting one of `bash`, `zsh` or `fish`".into()),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An error which can be returned when parsing command-line arguments.
+#[derive(Debug)]
+pub enum ArgsError {
+    MissingCommand,
+    UnknownCommand(String),
+    UnknownArguments(Vec<OsString>),
+    /// A flag that is only valid in combination with another flag, e.g. `--store` without `--dhat`.
+    MissingFlag(&'static str, &'static str),
+    /// Flags that cannot be combined, e.g. `--history` with a day selection.
+    ConflictingFlags(&'static str, &'static str),
+    Invalid(pico_args::Error),
+}
+
+impl Error for ArgsError {}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MissingCommand => {
+                write!(f, "no command specified, see `--help` for usage")
+            }
+            ArgsError::UnknownCommand(command) => {
+                write!(f, "unknown command `{command}`, see `--help` for usage")
+            }
+            ArgsError::UnknownArguments(args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>();
+                write!(f, "unexpected argument(s): {}", args.join(" "))
+            }
+            ArgsError::MissingFlag(flag, required) => {
+                write!(f, "`{flag}` can only be used together with `{required}`")
+            }
+            ArgsError::ConflictingFlags(flag, other) => {
+                write!(f, "`{flag}` cannot be combined with {other}")
+            }
+            ArgsError::Invalid(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<pico_args::Error> for ArgsError {
+    fn from(e: pico_args::Error) -> Self {
+        ArgsError::Invalid(e)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Describes a launcher subcommand. Used to render `--help` and shell completions.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub positional: Option<PositionalSpec>,
+    pub options: &'static [OptionSpec],
+}
+
+/// Describes the free argument of a subcommand.
+pub struct PositionalSpec {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub required: bool,
+}
+
+/// Describes a flag or option of a subcommand.
+pub struct OptionSpec {
+    pub flag: &'static str,
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+const DAY: PositionalSpec = PositionalSpec {
+    name: "DAY",
+    help: "Day number between 1 and 25",
+    required: true,
+};
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "scaffold",
+        about: "Create the solution, input and example files for a day.",
+        positional: Some(DAY),
+        options: &[
+            OptionSpec {
+                flag: "--download",
+                value: None,
+                help: "Download the input and puzzle description afterwards",
+            },
+            OptionSpec {
+                flag: "--overwrite",
+                value: None,
+                help: "Overwrite an existing solution file",
+            },
+        ],
+    },
+    CommandSpec {
+        name: "download",
+        about: "Download the input and puzzle description for a day via aoc-cli.",
+        positional: Some(DAY),
+        options: &[],
+    },
+    CommandSpec {
+        name: "read",
+        about: "Print the puzzle description for a day via aoc-cli.",
+        positional: Some(DAY),
+        options: &[],
+    },
+    CommandSpec {
+        name: "solve",
+        about: "Run the solutions for the selected days against their inputs.",
+        positional: Some(PositionalSpec {
+            name: "DAYS",
+            help: "Day selection, e.g. `1`, `1-5`, `odd` or `tag:grid`",
+            required: true,
+        }),
+        options: &[
+            OptionSpec {
+                flag: "--release",
+                value: None,
+                help: "Run an optimized build",
+            },
+            OptionSpec {
+                flag: "--dhat",
+                value: None,
+                help: "Profile heap allocations with DHAT",
+            },
+            OptionSpec {
+                flag: "--store",
+                value: None,
+                help: "Store the heap statistics in timings.json (requires `--dhat`)",
+            },
+            OptionSpec {
+                flag: "--variants",
+                value: None,
+                help: "Check that all registered variants agree and bench them against each other",
+            },
+            OptionSpec {
+                flag: "--visualize",
+                value: Some("TARGET"),
+                help: "Show recorded frames, `terminal`, `text` or `ppm` (images)",
+            },
+            OptionSpec {
+                flag: "--submit",
+                value: Some("PART"),
+                help: "Submit the result of part 1 or 2 via aoc-cli",
+            },
+            OptionSpec {
+                flag: "--option",
+                value: Some("KEY[=VALUE]"),
+                help: "Pass a puzzle-specific option to the solutions, can be repeated",
+            },
+        ],
+    },
+    CommandSpec {
+        name: "all",
+        about: "Run the solutions for all (or the selected) days.",
+        positional: Some(PositionalSpec {
+            name: "DAYS",
+            help: "Day selection, defaults to `all`",
+            required: false,
+        }),
+        options: &[OptionSpec {
+            flag: "--release",
+            value: None,
+            help: "Run an optimized build",
+        }],
+    },
+    CommandSpec {
+        name: "time",
+        about: "Benchmark the solutions for the selected days.",
+        positional: Some(PositionalSpec {
+            name: "DAYS",
+            help: "Day selection, defaults to `unsolved`",
+            required: false,
+        }),
+        options: &[
+            OptionSpec {
+                flag: "--all",
+                value: None,
+                help: "Bench all days, including the ones that are already stored",
+            },
+            OptionSpec {
+                flag: "--store",
+                value: None,
+                help: "Store the timings and update the benchmark report",
+            },
+            OptionSpec {
+                flag: "--sink",
+                value: Some("SINKS"),
+                help: "Report targets, e.g. `readme,csv` (`readme`, `markdown`, `csv`, `html`)",
+            },
+            OptionSpec {
+                flag: "--history",
+                value: Some("DAY"),
+                help: "Show how the stored timings of a day evolved, instead of benching",
+            },
+        ],
+    },
+    CommandSpec {
+        name: "stress",
+        about: "Bench a solution on generated inputs of growing size.",
+        positional: Some(DAY),
+        options: &[
+            OptionSpec {
+                flag: "--size",
+                value: Some("SIZE"),
+                help: "Largest input relative to the real input, defaults to `8x`",
+            },
+            OptionSpec {
+                flag: "--seed",
+                value: Some("SEED"),
+                help: "Seed of the input generator",
+            },
+        ],
+    },
+    CommandSpec {
+        name: "completions",
+        about: "Print a shell completion script.",
+        positional: Some(PositionalSpec {
+            name: "SHELL",
+            help: "One of `bash`, `zsh` or `fish`",
+            required: true,
+        }),
+        options: &[],
+    },
+    #[cfg(feature = "today")]
+    CommandSpec {
+        name: "today",
+        about: "Scaffold, download and read the current day.",
+        positional: None,
+        options: &[],
+    },
+];
+
+const SOLUTION_OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        flag: "--time",
+        value: None,
+        help: "Bench each part instead of running it once",
+    },
+    OptionSpec {
+        flag: "--submit",
+        value: Some("PART"),
+        help: "Submit the result of part 1 or 2 via aoc-cli",
+    },
+    OptionSpec {
+        flag: "--variants",
+        value: None,
+        help: "Check that all registered variants agree and bench them against each other",
+    },
+    OptionSpec {
+        flag: "--stress",
+        value: Some("SIZE"),
+        help: "Bench each part on generated inputs up to the given size, e.g. `10x`",
+    },
+    OptionSpec {
+        flag: "--seed",
+        value: Some("SEED"),
+        help: "Seed of the input generator (requires `--stress`)",
+    },
+    OptionSpec {
+        flag: "--visualize",
+        value: Some("TARGET"),
+        help: "Show recorded frames, `terminal`, `text` or `ppm` (images)",
+    },
+    OptionSpec {
+        flag: "--option",
+        value: Some("KEY[=VALUE]"),
+        help: "Pass a puzzle-specific option to the solution, can be repeated",
+    },
+];
+
+/// Options accepted by every command. They override values from `aoc.toml`.
+pub const GLOBAL_OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        flag: "--year",
+        value: Some("YEAR"),
+        help: "Puzzle year passed to aoc-cli",
+    },
+    OptionSpec {
+        flag: "--data-dir",
+        value: Some("PATH"),
+        help: "Directory for inputs, examples, puzzles and timings",
+    },
+    OptionSpec {
+        flag: "--format",
+        value: Some("FORMAT"),
+        help: "Output format of solution results, `text` or `json`",
+    },
+    OptionSpec {
+        flag: "--timeout",
+        value: Some("SECS"),
+        help: "Abort a solution after the given number of seconds",
+    },
+    OptionSpec {
+        flag: "--bench-budget",
+        value: Some("MS"),
+        help: "Approximate time spent benching a part",
+    },
+];
+
+const HELP_OPTION: OptionSpec = OptionSpec {
+    flag: "-h, --help",
+    value: None,
+    help: "Print help",
+};
+
+fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|c| c.name == name)
+}
+
+fn format_options<'a>(options: impl Iterator<Item = &'a OptionSpec>) -> Vec<String> {
+    let labels = options
+        .map(|o| match o.value {
+            Some(value) => (format!("{} <{value}>", o.flag), o.help),
+            None => (o.flag.to_string(), o.help),
+        })
+        .collect::<Vec<_>>();
+
+    let width = labels
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+
+    labels
+        .into_iter()
+        .map(|(label, help)| format!("  {label:width$}  {help}"))
+        .collect()
+}
+
+/// Renders the help text for the launcher, or for one of its subcommands.
+pub fn help(command: Option<&str>) -> String {
+    let Some(spec) = command.and_then(find_command) else {
+        let width = COMMANDS.iter().map(|c| c.name.len()).max().unwrap_or(0);
+        let mut lines = vec![
+            "Usage: cargo <COMMAND> [ARGS]".to_string(),
+            String::new(),
+            "Commands:".to_string(),
+        ];
+        lines.extend(
+            COMMANDS
+                .iter()
+                .map(|c| format!("  {:width$}  {}", c.name, c.about)),
+        );
+        lines.push(String::new());
+        lines.push("Global options:".into());
+        lines.extend(format_options(GLOBAL_OPTIONS.iter().chain([&HELP_OPTION])));
+        lines.push(String::new());
+        lines.push("See `cargo <COMMAND> --help` for details on a command.".into());
+        return lines.join("\n");
+    };
+
+    let mut usage = format!("Usage: cargo {}", spec.name);
+    if let Some(positional) = &spec.positional {
+        if positional.required {
+            usage.push_str(&format!(" <{}>", positional.name));
+        } else {
+            usage.push_str(&format!(" [{}]", positional.name));
+        }
+    }
+    usage.push_str(" [OPTIONS]");
+
+    let mut lines = vec![spec.about.to_string(), String::new(), usage];
+
+    if let Some(positional) = &spec.positional {
+        lines.push(String::new());
+        lines.push("Arguments:".into());
+        lines.push(format!("  <{}>  {}", positional.name, positional.help));
+    }
+
+    lines.push(String::new());
+    lines.push("Options:".into());
+    lines.extend(format_options(spec.options.iter().chain([&HELP_OPTION])));
+
+    lines.push(String::new());
+    lines.push("Global options:".into());
+    lines.extend(format_options(GLOBAL_OPTIONS.iter()));
+
+    lines.join("\n")
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl Cli {
+    /// Parses the launcher arguments from the process environment.
+    pub fn from_env() -> Result<Self, ArgsError> {
+        Self::from_vec(std::env::args_os().skip(1).collect())
+    }
+
+    /// Parses the launcher arguments from an explicit argument list (excluding the binary name).
+    pub fn from_vec(args: Vec<OsString>) -> Result<Self, ArgsError> {
+        // reject unknown flags up front, otherwise they would be consumed as free arguments.
+        if let Some(spec) = args
+            .first()
+            .and_then(|c| find_command(&c.to_string_lossy()))
+        {
+            let unknown = args
+                .iter()
+                .skip(1)
+                .filter(|arg| {
+                    let arg = arg.to_string_lossy();
+                    // options may also be passed as `--flag=value`.
+                    let flag = arg.split_once('=').map_or(&*arg, |(flag, _)| flag);
+                    flag.starts_with('-')
+                        && flag != "-h"
+                        && flag != "--help"
+                        && !spec
+                            .options
+                            .iter()
+                            .chain(GLOBAL_OPTIONS)
+                            .any(|o| o.flag == flag)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if !unknown.is_empty() {
+                return Err(ArgsError::UnknownArguments(unknown));
+            }
+        }
+
+        Self::parse(pico_args::Arguments::from_vec(args))
+    }
+
+    fn parse(mut args: pico_args::Arguments) -> Result<Self, ArgsError> {
+        let command = args.subcommand()?;
+        let overrides = ConfigOverrides::parse(&mut args)?;
+
+        let help_for = |topic: Option<&str>| {
+            Ok(Cli {
+                command: AppArguments::Help(help(topic)),
+                overrides: ConfigOverrides::default(),
+            })
+        };
+
+        let Some(command) = command else {
+            if args.contains(["-h", "--help"]) {
+                return help_for(None);
+            }
+            return Err(ArgsError::MissingCommand);
+        };
+
+        if command == "help" {
+            let topic: Option<String> = args.opt_free_from_str()?;
+            return help_for(topic.as_deref());
+        }
+
+        if find_command(&command).is_none() {
+            return Err(ArgsError::UnknownCommand(command));
+        }
+
+        if args.contains(["-h", "--help"]) {
+            return help_for(Some(&command));
+        }
+
+        let app_args = match command.as_str() {
+            "all" => AppArguments::All {
+                release: args.contains("--release"),
+                days: args.opt_free_from_str()?,
+            },
+            "time" => {
+                let store = args.contains("--store");
+                let sinks = args.opt_value_from_fn("--sink", parse_list)?;
+                if sinks.is_some() && !store {
+                    return Err(ArgsError::MissingFlag("--sink", "--store"));
+                }
+                let history = args.opt_value_from_str("--history")?;
+                let all = args.contains("--all");
+                let days = args.opt_free_from_str()?;
+                if history.is_some() && (store || all || days.is_some()) {
+                    return Err(ArgsError::ConflictingFlags("--history", "benching"));
+                }
+                AppArguments::Time {
+                    all,
+                    store,
+                    sinks,
+                    history,
+                    days,
+                }
+            }
+            "download" => AppArguments::Download {
+                day: args.free_from_str()?,
+            },
+            "read" => AppArguments::Read {
+                day: args.free_from_str()?,
+            },
+            "scaffold" => AppArguments::Scaffold {
+                download: args.contains("--download"),
+                overwrite: args.contains("--overwrite"),
+                day: args.free_from_str()?,
+            },
+            "solve" => {
+                let dhat = args.contains("--dhat");
+                let store = args.contains("--store");
+                if store && !dhat {
+                    return Err(ArgsError::MissingFlag("--store", "--dhat"));
+                }
+                let variants = args.contains("--variants");
+                let submit = args.opt_value_from_str("--submit")?;
+                if variants && (dhat || submit.is_some()) {
+                    return Err(ArgsError::ConflictingFlags(
+                        "--variants",
+                        "`--dhat` or `--submit`",
+                    ));
+                }
+                let visualize = args.opt_value_from_str("--visualize")?;
+                if visualize.is_some() && (dhat || variants) {
+                    return Err(ArgsError::ConflictingFlags(
+                        "--visualize",
+                        "`--dhat` or `--variants`",
+                    ));
+                }
+                AppArguments::Solve {
+                    release: args.contains("--release"),
+                    dhat,
+                    store,
+                    variants,
+                    visualize,
+                    submit,
+                    options: args.values_from_str("--option")?,
+                    days: args.free_from_str()?,
+                }
+            }
+            "stress" => AppArguments::Stress {
+                size: args.opt_value_from_str("--size")?.unwrap_or_default(),
+                seed: args.opt_value_from_str("--seed")?,
+                day: args.free_from_str()?,
+            },
+            "completions" => AppArguments::Completions {
+                shell: args.free_from_str()?,
+            },
+            #[cfg(feature = "today")]
+            "today" => AppArguments::Today,
+            _ => return Err(ArgsError::UnknownCommand(command)),
+        };
+
+        let remaining = args.finish();
+        if !remaining.is_empty() {
+            return Err(ArgsError::UnknownArguments(remaining));
+        }
+
+        Ok(Cli {
+            command: app_args,
+            overrides,
+        })
+    }
+}
+
+impl SolutionArguments {
+    /// Parses the solution arguments from the process environment, exiting on invalid input.
+    pub fn from_env() -> Self {
+        let mut args = pico_args::Arguments::from_env();
+
+        if args.contains(["-h", "--help"]) {
+            println!("Usage: cargo run --bin <DAY> -- [OPTIONS]\n\nOptions:");
+            let options = SOLUTION_OPTIONS
+                .iter()
+                .chain(GLOBAL_OPTIONS)
+                .chain([&HELP_OPTION]);
+            println!("{}", format_options(options).join("\n"));
+            std::process::exit(0);
+        }
+
+        match Self::parse(args) {
+            Ok(args) => {
+                options::init(&args.options);
+                args
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn parse(mut args: pico_args::Arguments) -> Result<Self, ArgsError> {
+        let parsed = Self {
+            time: args.contains("--time"),
+            submit: args.opt_value_from_str("--submit")?,
+            variants: args.contains("--variants"),
+            stress: args.opt_value_from_str("--stress")?,
+            seed: args.opt_value_from_str("--seed")?,
+            visualize: args.opt_value_from_str("--visualize")?,
+            options: args.values_from_str("--option")?,
+            overrides: ConfigOverrides::parse(&mut args)?,
+        };
+
+        // frames are never recorded while benching.
+        if parsed.visualize.is_some() && (parsed.time || parsed.variants || parsed.stress.is_some())
+        {
+            return Err(ArgsError::ConflictingFlags(
+                "--visualize",
+                "`--time`, `--variants` or `--stress`",
+            ));
+        }
+
+        if parsed.variants && parsed.submit.is_some() {
+            return Err(ArgsError::ConflictingFlags("--variants", "`--submit`"));
+        }
+
+        if parsed.stress.is_some() && (parsed.variants || parsed.submit.is_some()) {
+            return Err(ArgsError::ConflictingFlags(
+                "--stress",
+                "`--variants` or `--submit`",
+            ));
+        }
+
+        if parsed.seed.is_some() && parsed.stress.is_none() {
+            return Err(ArgsError::MissingFlag("--seed", "--stress"));
+        }
+
+        let remaining = args.finish();
+        if !remaining.is_empty() {
+            return Err(ArgsError::UnknownArguments(remaining));
+        }
+
+        Ok(parsed)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Renders a completion script for the `cargo` aliases of the launcher commands, e.g.
+/// `cargo solve`. Other cargo subcommands keep the completions of cargo itself.
+pub fn completions(shell: Shell) -> String {
+    let func = format!("_{}_cargo", env!("CARGO_PKG_NAME"));
+    let names = COMMANDS.iter().map(|c| c.name).collect::<Vec<_>>();
+
+    let words_of = |spec: &CommandSpec| {
+        let mut words = spec
+            .options
+            .iter()
+            .chain(GLOBAL_OPTIONS)
+            .map(|o| o.flag)
+            .chain(["--help"])
+            .collect::<Vec<_>>();
+        if spec.name == "completions" {
+            words.extend(["bash", "zsh", "fish"]);
+        }
+        words
+    };
+
+    match shell {
+        Shell::Bash => {
+            let mut lines = vec![
+                format!("{func}() {{"),
+                "    local cur=\"${COMP_WORDS[COMP_CWORD]}\"".into(),
+                "    if [ \"$COMP_CWORD\" -gt 1 ]; then".into(),
+                "        case \"${COMP_WORDS[1]}\" in".into(),
+            ];
+            for spec in COMMANDS {
+                lines.push(format!(
+                    "            {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                    spec.name,
+                    words_of(spec).join(" ")
+                ));
+            }
+            lines.extend([
+                "        esac".into(),
+                "    fi".into(),
+                "    declare -F _cargo >/dev/null && _cargo \"$@\"".into(),
+                "    if [ \"$COMP_CWORD\" -eq 1 ]; then".into(),
+                format!(
+                    "        COMPREPLY+=($(compgen -W \"{}\" -- \"$cur\"))",
+                    names.join(" ")
+                ),
+                "    fi".into(),
+                "}".into(),
+                // bash-completion loads the completions of cargo on first use.
+                "declare -F _completion_loader >/dev/null && _completion_loader cargo".into(),
+                format!("complete -o default -F {func} cargo"),
+            ]);
+            lines.join("\n")
+        }
+        Shell::Zsh => {
+            let mut lines = vec![
+                format!("{func}() {{"),
+                "    local -a commands".into(),
+                "    commands=(".into(),
+            ];
+            for spec in COMMANDS {
+                lines.push(format!("        '{}:{}'", spec.name, spec.about));
+            }
+            lines.extend([
+                "    )".into(),
+                "    if (( CURRENT > 2 )); then".into(),
+                "        case \"$words[2]\" in".into(),
+            ]);
+            for spec in COMMANDS {
+                lines.push(format!(
+                    "            {}) compadd -- {}; return ;;",
+                    spec.name,
+                    words_of(spec).join(" ")
+                ));
+            }
+            lines.extend([
+                "        esac".into(),
+                "    fi".into(),
+                "    (( CURRENT == 2 )) && _describe 'command' commands".into(),
+                "    (( $+functions[_cargo] )) && _cargo \"$@\"".into(),
+                "}".into(),
+                String::new(),
+                format!("compdef {func} cargo"),
+            ]);
+            lines.join("\n")
+        }
+        Shell::Fish => {
+            let mut lines = vec![];
+            for spec in COMMANDS {
+                lines.push(format!(
+                    "complete -c cargo -n '__fish_use_subcommand' -f -a {} -d '{}'",
+                    spec.name, spec.about
+                ));
+            }
+            for spec in COMMANDS {
+                lines.push(format!(
+                    "complete -c cargo -n '__fish_seen_subcommand_from {}' -f",
+                    spec.name
+                ));
+                for option in spec.options.iter().chain(GLOBAL_OPTIONS) {
+                    let value = if option.value.is_some() { " -r" } else { "" };
+                    lines.push(format!(
+                        "complete -c cargo -n '__fish_seen_subcommand_from {}' -l {}{value} -d '{}'",
+                        spec.name,
+                        option.flag.trim_start_matches("--"),
+                        option.help
+                    ));
+                }
+            }
+            lines.push(
+                "complete -c cargo -n '__fish_seen_subcommand_from completions' -a 'bash zsh fish'"
+                    .into(),
+            );
+            lines.join("\n")
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::{
+        completions, help, AppArguments, Cli, Part, Scale, Shell, SolutionArguments, COMMANDS,
+    };
+    use crate::day;
+    use crate::template::benchmark_report::Sink;
+    use crate::template::config::{ConfigOverrides, OutputFormat};
+    use crate::template::visualize::Visualization;
+
+    fn parse(args: &[&str]) -> Result<AppArguments, super::ArgsError> {
+        Cli::from_vec(args.iter().map(OsString::from).collect()).map(|cli| cli.command)
+    }
+
+    #[test]
+    fn parses_solve() {
+        let args = parse(&["solve", "1-3", "--release", "--submit", "2"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Solve {
+                days: "1-3".parse().unwrap(),
+                release: true,
+                dhat: false,
+                store: false,
+                variants: false,
+                visualize: None,
+                submit: Part::new(2),
+                options: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn parses_dhat_store() {
+        let args = parse(&["solve", "4", "--dhat", "--store"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Solve {
+                days: "4".parse().unwrap(),
+                release: false,
+                dhat: true,
+                store: true,
+                variants: false,
+                visualize: None,
+                submit: None,
+                options: vec![],
+            }
+        );
+        assert!(parse(&["solve", "4", "--store"]).is_err());
+    }
+
+    #[test]
+    fn parses_variants() {
+        let args = parse(&["solve", "13", "--variants", "--release"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Solve {
+                days: "13".parse().unwrap(),
+                release: true,
+                dhat: false,
+                store: false,
+                variants: true,
+                visualize: None,
+                submit: None,
+                options: vec![],
+            }
+        );
+        assert!(parse(&["solve", "13", "--variants", "--dhat"]).is_err());
+        assert!(parse(&["solve", "13", "--variants", "--submit", "1"]).is_err());
+    }
+
+    #[test]
+    fn parses_visualize() {
+        let args = parse(&["solve", "14", "--visualize", "terminal"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Solve {
+                days: "14".parse().unwrap(),
+                release: false,
+                dhat: false,
+                store: false,
+                variants: false,
+                visualize: Some(Visualization::Terminal),
+                submit: None,
+                options: vec![],
+            }
+        );
+        assert!(parse(&["solve", "14", "--visualize", "gif"]).is_err());
+        assert!(parse(&["solve", "14", "--visualize", "text", "--dhat"]).is_err());
+    }
+
+    #[test]
+    fn parses_stress() {
+        let args = parse(&["stress", "9", "--size", "10x", "--seed", "42"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Stress {
+                day: day!(9),
+                size: Scale::new(10).unwrap(),
+                seed: Some(42),
+            }
+        );
+        let args = parse(&["stress", "9"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Stress {
+                day: day!(9),
+                size: Scale::new(8).unwrap(),
+                seed: None,
+            }
+        );
+        assert!(parse(&["stress", "9", "--size", "0x"]).is_err());
+    }
+
+    #[test]
+    fn parses_optional_selection() {
+        let args = parse(&["time", "--store"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Time {
+                all: false,
+                days: None,
+                store: true,
+                sinks: None,
+                history: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_history() {
+        let args = parse(&["time", "--history", "7"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Time {
+                all: false,
+                days: None,
+                store: false,
+                sinks: None,
+                history: Some(day!(7)),
+            }
+        );
+        assert!(parse(&["time", "--history", "7", "--store"]).is_err());
+        assert!(parse(&["time", "1-3", "--history", "7"]).is_err());
+        assert!(parse(&["time", "--history", "26"]).is_err());
+    }
+
+    #[test]
+    fn parses_sinks() {
+        let args = parse(&["time", "1-5", "--store", "--sink", "csv,html"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Time {
+                all: false,
+                days: "1-5".parse().ok(),
+                store: true,
+                sinks: Some(vec![Sink::Csv, Sink::Html]),
+                history: None,
+            }
+        );
+        assert!(parse(&["time", "--sink", "csv"]).is_err());
+        assert!(parse(&["time", "--store", "--sink", "pdf"]).is_err());
+    }
+
+    #[test]
+    fn parses_scaffold() {
+        let args = parse(&["scaffold", "--download", "4"]).unwrap();
+        assert_eq!(
+            args,
+            AppArguments::Scaffold {
+                day: day!(4),
+                download: true,
+                overwrite: false,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_equals_form() {
+        let args = parse(&["solve", "1", "--submit=2", "--visualize=text"]).unwrap();
+        let AppArguments::Solve {
+            submit, visualize, ..
+        } = args
+        else {
+            panic!("expected solve");
+        };
+        assert_eq!(
+            (submit, visualize),
+            (Part::new(2), Some(Visualization::Text))
+        );
+        assert!(parse(&["time", "--store", "--sink=csv"]).is_ok());
+        assert!(parse(&["solve", "1", "--frobnicate=2"]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_submit_part() {
+        assert!(parse(&["solve", "1", "--submit", "3"]).is_err());
+        assert!(parse(&["solve", "1", "--submit"]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_input() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["frobnicate"]).is_err());
+        assert!(parse(&["all", "--verbose"]).is_err());
+        assert!(parse(&["time", "-v", "1"]).is_err());
+        assert!(parse(&["download"]).is_err());
+        assert!(parse(&["completions", "powershell"]).is_err());
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let cli = Cli::from_vec(
+            ["time", "--format", "json", "1", "--year", "2023"]
+                .iter()
+                .map(OsString::from)
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(cli.overrides.format, Some(OutputFormat::Json));
+        assert_eq!(cli.overrides.year, Some(2023));
+        assert!(parse(&["all", "--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn renders_help() {
+        let AppArguments::Help(text) = parse(&["solve", "--help"]).unwrap() else {
+            panic!("expected help");
+        };
+        assert!(text.contains("Usage: cargo solve <DAYS> [OPTIONS]"));
+        assert!(text.contains("--submit <PART>"));
+
+        let AppArguments::Help(text) = parse(&["--help"]).unwrap() else {
+            panic!("expected help");
+        };
+        assert_eq!(text, help(None));
+        assert!(text.contains("scaffold"));
+    }
+
+    #[test]
+    fn parses_solution_arguments() {
+        let parse = |args: &[&str]| {
+            SolutionArguments::parse(pico_args::Arguments::from_vec(
+                args.iter().map(OsString::from).collect(),
+            ))
+        };
+        assert_eq!(
+            parse(&["--time", "--submit", "1"]).unwrap(),
+            SolutionArguments {
+                time: true,
+                submit: Part::new(1),
+                variants: false,
+                stress: None,
+                seed: None,
+                visualize: None,
+                options: vec![],
+                overrides: ConfigOverrides::default(),
+            }
+        );
+        assert!(parse(&["--variants"]).unwrap().variants);
+        assert!(parse(&["--variants", "--submit", "1"]).is_err());
+        let stress = parse(&["--stress", "4x", "--seed", "7"]).unwrap();
+        assert_eq!((stress.stress, stress.seed), (Scale::new(4), Some(7)));
+        assert!(parse(&["--stress", "4x", "--variants"]).is_err());
+        assert!(parse(&["--seed", "7"]).is_err());
+        assert_eq!(
+            parse(&["--visualize", "ppm"]).unwrap().visualize,
+            Some(Visualization::Ppm)
+        );
+        assert!(parse(&["--visualize", "text", "--time"]).is_err());
+        assert_eq!(
+            parse(&["--timeout", "5"]).unwrap().overrides.timeout_secs,
+            Some(5)
+        );
+        assert!(parse(&["--submit", "0"]).is_err());
+        assert!(parse(&["--foo"]).is_err());
+    }
+
+    #[test]
+    fn renders_completions() {
+        let bash = completions(Shell::Bash);
+        assert!(bash.ends_with("complete -o default -F _advent_of_code_cargo cargo"));
+        let solve = bash.lines().find(|line| line.contains("solve)")).unwrap();
+        assert!(solve.contains("--submit"));
+        assert!(completions(Shell::Zsh).ends_with("compdef _advent_of_code_cargo cargo"));
+        let fish = completions(Shell::Fish);
+        assert!(
+            fish.contains("complete -c cargo -n '__fish_seen_subcommand_from solve' -l submit -r")
+        );
+    }
+
+    #[test]
+    fn completes_cargo_aliases() {
+        // the completions register for `cargo <command>`, which has to be an alias of the command.
+        let aliases = include_str!("../../.cargo/config.toml");
+        for spec in COMMANDS {
+            let alias = aliases
+                .lines()
+                .find(|line| line.starts_with(&format!("{} = ", spec.name)))
+                .unwrap_or_else(|| panic!("`{}` should have a cargo alias", spec.name));
+            assert!(alias.ends_with(&format!("-- {}\"", spec.name)), "{alias}");
+        }
+    }
+}
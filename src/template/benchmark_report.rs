@@ -0,0 +1,597 @@
+/// Renders stored timings to the benchmark report sinks: the readme table, a standalone
+/// markdown file, a CSV file and an HTML report.
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::template::config::Config;
+use crate::template::memory::{format_bytes, HeapSummary};
+use crate::template::run_multi::get_path_for_bin;
+use crate::template::stars::Stars;
+use crate::template::timings::{Timing, Timings};
+use crate::template::{readme, readme_benchmarks};
+
+/// A column of the benchmark table. The `Day` column is always shown first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// Average run time of part 1.
+    Part1,
+    /// Average run time of part 2.
+    Part2,
+    /// Average run time of both parts.
+    Total,
+    /// Sum of the median run times of both parts.
+    Median,
+    /// Sum of the fastest run times of both parts.
+    Min,
+    /// Number of bench samples.
+    Samples,
+    /// Peak heap of both parts, only shown when heap statistics were stored.
+    Memory,
+    /// One star per part solved, as recorded in `stars.json`.
+    Stars,
+    /// Share of the day in the total run time.
+    Share,
+    /// Total run time relative to the slowest day, as a unicode bar.
+    Chart,
+}
+
+/// The columns used when none are configured.
+pub const DEFAULT_COLUMNS: [Column; 3] = [Column::Part1, Column::Part2, Column::Memory];
+
+/// An output target of `time --store`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sink {
+    /// The table between the markers in the readme.
+    Readme,
+    /// A standalone markdown file, `BENCHMARKS.md` by default.
+    Markdown,
+    Csv,
+    Html,
+}
+
+/// Width of a full bar in the `chart` column, in characters.
+const CHART_WIDTH: f64 = 20.0;
+static CHART_BLOCKS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// A rendered table cell. Code cells are wrapped in backticks in markdown, one per value.
+enum Cell {
+    Code(Vec<String>),
+    Text(String),
+}
+
+impl Cell {
+    fn plain(&self) -> String {
+        match self {
+            Cell::Code(values) => values.join(" / "),
+            Cell::Text(value) => value.clone(),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        match self {
+            Cell::Code(values) => values
+                .iter()
+                .map(|value| format!("`{value}`"))
+                .collect::<Vec<_>>()
+                .join(" / "),
+            Cell::Text(value) => value.clone(),
+        }
+    }
+}
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Column::Part1 => "Part 1",
+            Column::Part2 => "Part 2",
+            Column::Total => "Total",
+            Column::Median => "Median",
+            Column::Min => "Min",
+            Column::Samples => "Samples",
+            Column::Memory => "Peak heap",
+            Column::Stars => "Stars",
+            Column::Share => "Share",
+            Column::Chart => "Chart",
+        }
+    }
+
+    fn cell(self, timing: &Timing, timings: &Timings, stars: &Stars) -> Cell {
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".into());
+
+        match self {
+            Column::Part1 => Cell::Code(vec![or_dash(timing.part_1.clone())]),
+            Column::Part2 => Cell::Code(vec![or_dash(timing.part_2.clone())]),
+            Column::Total => Cell::Code(vec![format_nanos(timing.total_nanos)]),
+            Column::Median => Cell::Code(vec![or_dash(timing.median_nanos.map(format_nanos))]),
+            Column::Min => Cell::Code(vec![or_dash(timing.min_nanos.map(format_nanos))]),
+            Column::Samples => Cell::Text(or_dash(timing.samples.map(|x| x.to_string()))),
+            Column::Memory => {
+                let format_peak =
+                    |heap: Option<HeapSummary>| or_dash(heap.map(|h| format_bytes(h.peak_bytes)));
+                Cell::Code(vec![
+                    format_peak(timing.part_1_heap),
+                    format_peak(timing.part_2_heap),
+                ])
+            }
+            Column::Stars => {
+                let stars = (1..=2)
+                    .filter(|part| stars.find(timing.day, *part).is_some())
+                    .count();
+                Cell::Text(if stars == 0 {
+                    "-".into()
+                } else {
+                    "⭐".repeat(stars)
+                })
+            }
+            Column::Share => {
+                let total = timings.data.iter().map(|t| t.total_nanos).sum::<f64>();
+                Cell::Text(if total > 0.0 {
+                    format!("{:.1}%", timing.total_nanos / total * 100.0)
+                } else {
+                    "-".into()
+                })
+            }
+            Column::Chart => {
+                let max = timings
+                    .data
+                    .iter()
+                    .map(|t| t.total_nanos)
+                    .fold(0_f64, f64::max);
+                Cell::Text(if max > 0.0 {
+                    render_bar(timing.total_nanos / max)
+                } else {
+                    String::new()
+                })
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_nanos(nanos: f64) -> String {
+    format!("{:.1?}", Duration::from_nanos(nanos as u64))
+}
+
+/// Renders a bar of up to [`CHART_WIDTH`] characters with a resolution of 1/8th character.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn render_bar(fraction: f64) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * CHART_WIDTH * 8.0).round() as usize;
+    let mut bar = CHART_BLOCKS[7].to_string().repeat(eighths / 8);
+    if let Some(partial) = (eighths % 8).checked_sub(1) {
+        bar.push(CHART_BLOCKS[partial]);
+    }
+    if bar.is_empty() {
+        // always render a sliver, so that very fast days remain visible.
+        bar.push(CHART_BLOCKS[0]);
+    }
+    bar
+}
+
+/// Drops the memory column when no heap statistics have been stored.
+fn visible_columns(columns: &[Column], timings: &Timings) -> Vec<Column> {
+    columns
+        .iter()
+        .copied()
+        .filter(|column| *column != Column::Memory || timings.has_heap())
+        .collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Renders the heading, the markdown table and the total run time.
+pub(crate) fn markdown_table(
+    heading: &str,
+    timings: &Timings,
+    stars: &Stars,
+    columns: &[Column],
+) -> Vec<String> {
+    let columns = visible_columns(columns, timings);
+
+    let headers = columns.iter().map(|c| c.header()).collect::<Vec<_>>();
+    let mut lines: Vec<String> = vec![
+        heading.into(),
+        String::new(),
+        format!("| Day | {} |", headers.join(" | ")),
+        format!("| {}  |", vec![":---:"; columns.len() + 1].join(" | ")),
+    ];
+
+    for timing in &timings.data {
+        let cells = columns
+            .iter()
+            .map(|column| column.cell(timing, timings, stars).markdown())
+            .collect::<Vec<_>>();
+        lines.push(format!(
+            "| [Day {}]({}) | {} |",
+            timing.day.into_inner(),
+            get_path_for_bin(timing.day),
+            cells.join(" | ")
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(format!("**Total: {:.2}ms**", timings.total_millis()));
+
+    lines
+}
+
+/// Renders the table as CSV, one row per day.
+pub(crate) fn csv(timings: &Timings, stars: &Stars, columns: &[Column]) -> String {
+    let columns = visible_columns(columns, timings);
+
+    let escape = |value: String| {
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    };
+
+    let mut lines = vec![std::iter::once("day".to_string())
+        .chain(columns.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(",")];
+
+    for timing in &timings.data {
+        lines.push(
+            std::iter::once(timing.day.into_inner().to_string())
+                .chain(
+                    columns
+                        .iter()
+                        .map(|column| escape(column.cell(timing, timings, stars).plain())),
+                )
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Renders the table as a standalone HTML document.
+pub(crate) fn html(heading: &str, timings: &Timings, stars: &Stars, columns: &[Column]) -> String {
+    let columns = visible_columns(columns, timings);
+
+    let escape = |value: &str| {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+
+    let title = escape(heading.trim_start_matches('#').trim());
+
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html>".into(),
+        "<head>".into(),
+        "<meta charset=\"utf-8\">".into(),
+        format!("<title>{title}</title>"),
+        "<style>table { border-collapse: collapse; } th, td { padding: 4px 12px; text-align: center; } td.chart { text-align: left; }</style>".into(),
+        "</head>".into(),
+        "<body>".into(),
+        format!("<h2>{title}</h2>"),
+        "<table>".into(),
+    ];
+
+    let headers = columns
+        .iter()
+        .map(|column| format!("<th>{}</th>", column.header()))
+        .collect::<String>();
+    lines.push(format!("<tr><th>Day</th>{headers}</tr>"));
+
+    for timing in &timings.data {
+        let cells = columns
+            .iter()
+            .map(|column| {
+                let class = if *column == Column::Chart {
+                    " class=\"chart\""
+                } else {
+                    ""
+                };
+                let value = escape(&column.cell(timing, timings, stars).plain());
+                format!("<td{class}>{value}</td>")
+            })
+            .collect::<String>();
+        lines.push(format!(
+            "<tr><td><a href=\"{}\">Day {}</a></td>{cells}</tr>",
+            get_path_for_bin(timing.day),
+            timing.day.into_inner()
+        ));
+    }
+
+    lines.push("</table>".into());
+    lines.push(format!(
+        "<p><strong>Total: {:.2}ms</strong></p>",
+        timings.total_millis()
+    ));
+    lines.push("</body>".into());
+    lines.push("</html>".into());
+
+    lines.join("\n") + "\n"
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Writes the timings to every sink. Falls back to the configured sinks when `sinks` is `None`.
+/// Prints one line per sink, errors are reported but do not abort the remaining sinks.
+pub(crate) fn store(timings: &Timings, config: &Config, sinks: Option<&[Sink]>) {
+    let sinks = sinks.map_or_else(|| config.report_sinks(), <[Sink]>::to_vec);
+
+    if sinks.is_empty() {
+        println!("Stored updated timings.");
+        return;
+    }
+
+    let stars = Stars::read_from_file(config).unwrap_or_default();
+
+    for sink in sinks {
+        let path = sink.path(config);
+        match write(sink, timings, &stars, config) {
+            Ok(()) => println!("Updated {}.", path.display()),
+            Err(e) => eprintln!("Failed to update {}: {e}", path.display()),
+        }
+    }
+}
+
+fn write(
+    sink: Sink,
+    timings: &Timings,
+    stars: &Stars,
+    config: &Config,
+) -> Result<(), readme::Error> {
+    let columns = &config.report.columns;
+    let heading = &config.readme.heading;
+
+    match sink {
+        Sink::Readme => readme_benchmarks::update(timings, stars, columns, &config.readme),
+        Sink::Markdown => {
+            let content = markdown_table(heading, timings, stars, columns).join("\n") + "\n";
+            write_file(&config.report.markdown_path, &content)
+        }
+        Sink::Csv => write_file(&config.report.csv_path, &csv(timings, stars, columns)),
+        Sink::Html => write_file(
+            &config.report.html_path,
+            &html(heading, timings, stars, columns),
+        ),
+    }
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), readme::Error> {
+    fs::write(path, content)?;
+    Ok(())
+}
+
+impl Sink {
+    fn path(self, config: &Config) -> &Path {
+        match self {
+            Sink::Readme => &config.readme.path,
+            Sink::Markdown => &config.report.markdown_path,
+            Sink::Csv => &config.report.csv_path,
+            Sink::Html => &config.report.html_path,
+        }
+    }
+}
+
+/// Parses a comma-separated list, e.g. `readme,csv`.
+pub fn parse_list<T: FromStr>(s: &str) -> Result<Vec<T>, T::Err> {
+    s.split(',').map(|term| term.trim().parse()).collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl FromStr for Column {
+    type Err = ReportFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "part_1" => Ok(Column::Part1),
+            "part_2" => Ok(Column::Part2),
+            "total" => Ok(Column::Total),
+            "median" => Ok(Column::Median),
+            "min" => Ok(Column::Min),
+            "samples" => Ok(Column::Samples),
+            "memory" => Ok(Column::Memory),
+            "stars" => Ok(Column::Stars),
+            "share" => Ok(Column::Share),
+            "chart" => Ok(Column::Chart),
+            _ => Err(ReportFromStrError::Column(s.to_string())),
+        }
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Column::Part1 => "part_1",
+            Column::Part2 => "part_2",
+            Column::Total => "total",
+            Column::Median => "median",
+            Column::Min => "min",
+            Column::Samples => "samples",
+            Column::Memory => "memory",
+            Column::Stars => "stars",
+            Column::Share => "share",
+            Column::Chart => "chart",
+        })
+    }
+}
+
+impl FromStr for Sink {
+    type Err = ReportFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "readme" => Ok(Sink::Readme),
+            "markdown" => Ok(Sink::Markdown),
+            "csv" => Ok(Sink::Csv),
+            "html" => Ok(Sink::Html),
+            _ => Err(ReportFromStrError::Sink(s.to_string())),
+        }
+    }
+}
+
+impl Display for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Sink::Readme => "readme",
+            Sink::Markdown => "markdown",
+            Sink::Csv => "csv",
+            Sink::Html => "html",
+        })
+    }
+}
+
+/// An error which can be returned when parsing a [`Column`] or a [`Sink`].
+#[derive(Debug)]
+pub enum ReportFromStrError {
+    Column(String),
+    Sink(String),
+}
+
+impl Error for ReportFromStrError {}
+
+impl Display for ReportFromStrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFromStrError::Column(s) => write!(
+                f,
+                "invalid column `{s}`, expecting `part_1`, `part_2`, `total`, `median`, `min`, \
+                `samples`, `memory`, `stars`, `share` or `chart`"
+            ),
+            ReportFromStrError::Sink(s) => write!(
+                f,
+                "invalid sink `{s}`, expecting `readme`, `markdown`, `csv` or `html`"
+            ),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{csv, html, markdown_table, parse_list, render_bar, Column, Sink};
+    use crate::template::stars::Stars;
+    use crate::{day, template::timings::Timing, template::timings::Timings};
+
+    /// Day 1 is solved, day 2 was timed but has no stars yet.
+    fn get_mock_stars() -> Stars {
+        let mut stars = Stars::default();
+        stars.record(day!(1), 1, None);
+        stars.record(day!(1), 2, None);
+        stars
+    }
+
+    fn get_mock_timings() -> Timings {
+        Timings {
+            data: vec![
+                Timing {
+                    day: day!(1),
+                    part_1: Some("10.0ms".into()),
+                    part_2: Some("20.0ms".into()),
+                    total_nanos: 3e+7,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: Some(2.9e+7),
+                    min_nanos: Some(2.5e+7),
+                    samples: Some(33),
+                },
+                Timing {
+                    day: day!(2),
+                    part_1: Some("10.0ms".into()),
+                    part_2: None,
+                    total_nanos: 1e+7,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn parses_columns_and_sinks() {
+        assert_eq!(
+            parse_list::<Column>("part_1, median,chart").unwrap(),
+            vec![Column::Part1, Column::Median, Column::Chart]
+        );
+        assert_eq!(
+            parse_list::<Sink>("readme,html").unwrap(),
+            vec![Sink::Readme, Sink::Html]
+        );
+        assert!(parse_list::<Column>("part_3").is_err());
+        assert!(parse_list::<Sink>("pdf").is_err());
+    }
+
+    #[test]
+    fn renders_bars() {
+        assert_eq!(render_bar(1.0), "████████████████████");
+        assert_eq!(render_bar(0.5), "██████████");
+        assert_eq!(render_bar(0.0125), "▎");
+        assert_eq!(render_bar(0.0), "▏");
+    }
+
+    #[test]
+    fn renders_markdown_columns() {
+        let columns = [
+            Column::Total,
+            Column::Median,
+            Column::Min,
+            Column::Samples,
+            Column::Memory,
+            Column::Stars,
+            Column::Share,
+            Column::Chart,
+        ];
+        let lines = markdown_table(
+            "### Timings",
+            &get_mock_timings(),
+            &get_mock_stars(),
+            &columns,
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "### Timings",
+                "",
+                "| Day | Total | Median | Min | Samples | Stars | Share | Chart |",
+                "| :---: | :---: | :---: | :---: | :---: | :---: | :---: | :---:  |",
+                "| [Day 1](./src/bin/01.rs) | `30.0ms` | `29.0ms` | `25.0ms` | 33 | ⭐⭐ | 75.0% | ████████████████████ |",
+                "| [Day 2](./src/bin/02.rs) | `10.0ms` | `-` | `-` | - | - | 25.0% | ██████▋ |",
+                "",
+                "**Total: 40.00ms**",
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_csv() {
+        let columns = [Column::Part1, Column::Part2, Column::Stars];
+        assert_eq!(
+            csv(&get_mock_timings(), &get_mock_stars(), &columns),
+            "day,part_1,part_2,stars\n1,10.0ms,20.0ms,⭐⭐\n2,10.0ms,-,-\n"
+        );
+    }
+
+    #[test]
+    fn renders_html() {
+        let report = html(
+            "## Benchmarks",
+            &get_mock_timings(),
+            &Stars::default(),
+            &[Column::Part1],
+        );
+        assert!(report.contains("<h2>Benchmarks</h2>"));
+        assert!(report.contains("<tr><th>Day</th><th>Part 1</th></tr>"));
+        assert!(report
+            .contains("<tr><td><a href=\"./src/bin/01.rs\">Day 1</a></td><td>10.0ms</td></tr>"));
+        assert!(report.contains("<p><strong>Total: 40.00ms</strong></p>"));
+    }
+}
@@ -1,5 +1,17 @@
-use crate::template::{all_days, run_multi::run_multi};
+use crate::template::config::{Config, ConfigOverrides};
+use crate::template::{run_multi::run_multi, DaySelection};
 
-pub fn handle(is_release: bool) {
-    run_multi(&all_days().collect(), is_release, false);
+pub fn handle(
+    selection: &DaySelection,
+    is_release: bool,
+    config: &Config,
+    overrides: &ConfigOverrides,
+) {
+    run_multi(
+        &selection.resolve(config),
+        is_release,
+        false,
+        false,
+        overrides,
+    );
 }
@@ -1,28 +1,138 @@
-use std::process::{Command, Stdio};
+use std::collections::HashSet;
+use std::process::{self, Command, Stdio};
 
-use crate::template::Day;
+use crate::template::args::Part;
+use crate::template::config::{Config, ConfigOverrides, OutputFormat};
+use crate::template::options::SolutionOption;
+use crate::template::run_multi::{bin_exists, child_commands};
+use crate::template::timings::Timings;
+use crate::template::visualize::Visualization;
+use crate::template::{all_days, benchmark_report, Day, DaySelection, ANSI_BOLD, ANSI_RESET};
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
-    let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
+#[allow(clippy::too_many_arguments)]
+pub fn handle(
+    selection: &DaySelection,
+    release: bool,
+    dhat: bool,
+    store: bool,
+    variants: bool,
+    visualize: Option<Visualization>,
+    submit_part: Option<Part>,
+    options: &[SolutionOption],
+    config: &Config,
+    overrides: &ConfigOverrides,
+) {
+    let days_to_run = selection.resolve(config);
 
-    if dhat {
-        cmd_args.extend([
-            "--profile".to_string(),
-            "dhat".to_string(),
-            "--features".to_string(),
-            "dhat-heap".to_string(),
-        ]);
-    } else if release {
-        cmd_args.push("--release".to_string());
+    if submit_part.is_some() && days_to_run.len() != 1 {
+        eprintln!("`--submit` can only be used when solving a single day.");
+        process::exit(1);
     }
 
-    cmd_args.push("--".to_string());
+    // flags that are forwarded to the solution binaries.
+    let mut solution_args = vec![];
 
     if let Some(submit_part) = submit_part {
-        cmd_args.push("--submit".to_string());
-        cmd_args.push(submit_part.to_string());
+        solution_args.extend(["--submit".to_string(), submit_part.to_string()]);
     }
 
+    if variants {
+        solution_args.push("--variants".to_string());
+    }
+
+    if let Some(visualize) = visualize {
+        solution_args.extend(["--visualize".to_string(), visualize.to_string()]);
+    }
+
+    for option in options {
+        solution_args.extend(["--option".to_string(), option.to_string()]);
+    }
+
+    if store {
+        let timings = run_stored(&days_to_run, &solution_args, overrides);
+        store_heap(&timings, config);
+        return;
+    }
+
+    if days_to_run.len() == 1 {
+        let day = *days_to_run.iter().next().unwrap();
+        run_solution(day, release, dhat, &solution_args, overrides);
+        return;
+    }
+
+    let mut need_space = false;
+
+    // NOTE: use non-duplicate, sorted day values and skip days that have not been scaffolded yet.
+    all_days()
+        .filter(|day| days_to_run.contains(day))
+        .filter(|day| bin_exists(*day))
+        .for_each(|day| {
+            if need_space {
+                println!();
+            }
+            need_space = true;
+
+            println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+            println!("------");
+
+            run_solution(day, release, dhat, &solution_args, overrides);
+        });
+}
+
+/// Run the selected days with `--dhat`, collecting the heap statistics of every part.
+fn run_stored(
+    days_to_run: &HashSet<Day>,
+    solution_args: &[String],
+    overrides: &ConfigOverrides,
+) -> Timings {
+    // NOTE: the output of child commands is parsed, so it always has to be text.
+    let overrides = ConfigOverrides {
+        format: Some(OutputFormat::Text),
+        ..overrides.clone()
+    };
+
+    let mut need_space = false;
+
+    let data = all_days()
+        .filter(|day| days_to_run.contains(day))
+        .filter(|day| bin_exists(*day))
+        .map(|day| {
+            if need_space {
+                println!();
+            }
+            need_space = true;
+
+            if days_to_run.len() > 1 {
+                println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+                println!("------");
+            }
+
+            let args = build_args(day, false, true, solution_args, &overrides);
+            let output = child_commands::run_cargo(&args).unwrap();
+            child_commands::parse_exec_time(&output, day)
+        })
+        .collect();
+
+    Timings { data }
+}
+
+fn store_heap(timings: &Timings, config: &Config) {
+    let merged_timings = Timings::read_from_file(config).merge_heap(timings);
+    merged_timings.store_file(config).unwrap();
+
+    println!();
+    benchmark_report::store(&merged_timings, config, None);
+}
+
+fn run_solution(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    solution_args: &[String],
+    overrides: &ConfigOverrides,
+) {
+    let cmd_args = build_args(day, release, dhat, solution_args, overrides);
+
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
         .stdout(Stdio::inherit())
@@ -32,3 +142,29 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
 
     cmd.wait().unwrap();
 }
+
+fn build_args(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    solution_args: &[String],
+    overrides: &ConfigOverrides,
+) -> Vec<String> {
+    let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
+
+    if dhat {
+        cmd_args.extend([
+            "--profile".to_string(),
+            "dhat".to_string(),
+            "--features".to_string(),
+            "dhat-heap".to_string(),
+        ]);
+    } else if release {
+        cmd_args.push("--release".to_string());
+    }
+
+    cmd_args.push("--".to_string());
+    cmd_args.extend_from_slice(solution_args);
+    cmd_args.extend(overrides.to_args());
+    cmd_args
+}
@@ -0,0 +1,41 @@
+use std::process::{self, Command, Stdio};
+
+use crate::template::args::Scale;
+use crate::template::config::ConfigOverrides;
+use crate::template::run_multi::bin_exists;
+use crate::template::Day;
+
+pub fn handle(day: Day, size: Scale, seed: Option<u64>, overrides: &ConfigOverrides) {
+    if !bin_exists(day) {
+        eprintln!("Day {day} has not been scaffolded yet.");
+        process::exit(1);
+    }
+
+    let mut cmd_args = vec![
+        "run".to_string(),
+        "--quiet".to_string(),
+        "--release".to_string(),
+        "--bin".to_string(),
+        day.to_string(),
+        "--".to_string(),
+        "--stress".to_string(),
+        size.to_string(),
+    ];
+
+    if let Some(seed) = seed {
+        cmd_args.extend(["--seed".to_string(), seed.to_string()]);
+    }
+
+    cmd_args.extend(overrides.to_args());
+
+    let status = Command::new("cargo")
+        .args(&cmd_args)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .unwrap();
+
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+}
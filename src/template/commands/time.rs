@@ -1,39 +1,77 @@
-use std::collections::HashSet;
+use tinyjson::JsonValue;
 
+use crate::template::benchmark_report::{self, Sink};
+use crate::template::config::{Config, ConfigOverrides, OutputFormat};
+use crate::template::history::{self, Environment};
 use crate::template::run_multi::run_multi;
 use crate::template::timings::Timings;
-use crate::template::{all_days, readme_benchmarks, Day};
-
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
-    let stored_timings = Timings::read_from_file();
-
-    let days_to_run = day.map_or_else(
-        || {
-            if run_all {
-                all_days().collect()
-            } else {
-                // when the `--all` flag is not set, filter out days that are fully benched.
-                all_days()
-                    .filter(|day| !stored_timings.is_day_complete(*day))
-                    .collect()
-            }
-        },
-        |day| HashSet::from([day]),
-    );
+use crate::template::{Day, DayFilter, DaySelection};
+
+pub fn handle(
+    selection: Option<DaySelection>,
+    run_all: bool,
+    store: bool,
+    sinks: Option<&[Sink]>,
+    config: &Config,
+    overrides: &ConfigOverrides,
+) {
+    let stored_timings = Timings::read_from_file(config);
+
+    let selection = selection.unwrap_or_else(|| {
+        if run_all {
+            DaySelection::all()
+        } else {
+            // when the `--all` flag is not set, filter out days that are fully benched.
+            DaySelection::from(DayFilter::Unsolved)
+        }
+    });
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let days_to_run = selection.resolve_with(&stored_timings);
 
-    if store {
+    // collected before anything is written, so that the new timings don't mark the commit dirty.
+    let environment = store.then(|| Environment::current(config));
+
+    let timings = run_multi(
+        &days_to_run,
+        true,
+        true,
+        config.bench.count_allocations,
+        overrides,
+    )
+    .unwrap();
+
+    if let Some(environment) = environment {
         let merged_timings = stored_timings.merge(&timings);
-        merged_timings.store_file().unwrap();
+        merged_timings.store_file(config).unwrap();
+
+        if let Err(e) = history::append(&timings, &environment, config) {
+            eprintln!("Failed to append to the benchmark history: {e}");
+        }
 
         println!();
-        match readme_benchmarks::update(merged_timings) {
-            Ok(()) => {
-                println!("Stored updated benchmarks.");
-            }
-            Err(_) => {
-                eprintln!("Failed to store updated benchmarks.");
+        benchmark_report::store(&merged_timings, config, sinks);
+    }
+}
+
+/// Prints how the timings of `day` evolved over all stored benchmarks.
+pub fn history(day: Day, config: &Config) {
+    let entries = history::read(day, config);
+
+    if entries.is_empty() {
+        println!(
+            "No benchmark history for day {day}. Run `cargo time {day} --store` to record one."
+        );
+        return;
+    }
+
+    match config.format {
+        OutputFormat::Text => println!("{}", history::render(day, &entries)),
+        OutputFormat::Json => {
+            for entry in &entries {
+                match JsonValue::from(entry).stringify() {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => eprintln!("Could not serialize entry: {e}"),
+                }
             }
         }
     }
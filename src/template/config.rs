@@ -0,0 +1,600 @@
+/// Project configuration, read from `aoc.toml` in the repository root.
+///
+/// Only the subset of TOML used by the config is supported: `[sections]`, `key = value` pairs
+/// with string, integer, float, boolean and single-line array values, and `#` comments.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs, io};
+
+use crate::template::benchmark_report::{parse_list, Column, Sink, DEFAULT_COLUMNS};
+use crate::template::Day;
+
+static CONFIG_FILE_PATH: &str = "aoc.toml";
+
+/// The output format of solution results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable output.
+    #[default]
+    Text,
+    /// One JSON object per part.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err("expecting `text` or `json`".into()),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => f.write_str("text"),
+            OutputFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+/// Settings for `--time` benchmarks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchConfig {
+    /// Approximate total time spent benching a part.
+    pub budget: Duration,
+    pub min_samples: u128,
+    pub max_samples: u128,
+    /// Whether `cargo time` builds solutions with the `count-alloc` feature.
+    pub count_allocations: bool,
+}
+
+/// Settings for the readme benchmark and stars tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadmeConfig {
+    /// Whether `time --store` updates the readme.
+    pub enabled: bool,
+    /// Whether an accepted `--submit` updates the stars table in the readme.
+    pub stars: bool,
+    pub path: PathBuf,
+    pub heading: String,
+}
+
+/// Settings for the benchmark report written by `time --store`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportConfig {
+    pub columns: Vec<Column>,
+    /// Sinks used when `--sink` is not passed.
+    pub sinks: Vec<Sink>,
+    pub markdown_path: PathBuf,
+    pub csv_path: PathBuf,
+    pub html_path: PathBuf,
+}
+
+/// Runner configuration. Defaults mirror the behavior without a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub year: Option<u16>,
+    pub data_dir: PathBuf,
+    pub session_file: Option<PathBuf>,
+    pub format: OutputFormat,
+    /// Wall time after which a solution binary is aborted.
+    pub timeout: Option<Duration>,
+    pub bench: BenchConfig,
+    pub readme: ReadmeConfig,
+    pub report: ReportConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            year: None,
+            data_dir: PathBuf::from("data"),
+            session_file: None,
+            format: OutputFormat::Text,
+            timeout: None,
+            bench: BenchConfig {
+                budget: Duration::from_secs(1),
+                min_samples: 10,
+                max_samples: 10000,
+                count_allocations: false,
+            },
+            readme: ReadmeConfig {
+                enabled: true,
+                stars: true,
+                path: PathBuf::from("README.md"),
+                heading: "## Benchmarks".into(),
+            },
+            report: ReportConfig {
+                columns: DEFAULT_COLUMNS.to_vec(),
+                sinks: vec![Sink::Readme],
+                markdown_path: PathBuf::from("BENCHMARKS.md"),
+                csv_path: PathBuf::from("benchmarks.csv"),
+                html_path: PathBuf::from("benchmarks.html"),
+            },
+        }
+    }
+}
+
+/// Values passed on the command-line that take precedence over the config file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigOverrides {
+    pub year: Option<u16>,
+    pub data_dir: Option<PathBuf>,
+    pub format: Option<OutputFormat>,
+    pub timeout_secs: Option<u64>,
+    pub bench_budget_ms: Option<u64>,
+}
+
+impl ConfigOverrides {
+    /// Parses the override flags, which are accepted by every command.
+    pub fn parse(args: &mut pico_args::Arguments) -> Result<Self, pico_args::Error> {
+        Ok(Self {
+            year: args.opt_value_from_str("--year")?,
+            data_dir: args.opt_value_from_str("--data-dir")?,
+            format: args.opt_value_from_str("--format")?,
+            timeout_secs: args.opt_value_from_str("--timeout")?,
+            bench_budget_ms: args.opt_value_from_str("--bench-budget")?,
+        })
+    }
+
+    /// Converts the overrides back to flags, used to forward them to solution binaries.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if let Some(year) = self.year {
+            args.extend(["--year".into(), year.to_string()]);
+        }
+        if let Some(data_dir) = &self.data_dir {
+            args.extend(["--data-dir".into(), data_dir.to_string_lossy().to_string()]);
+        }
+        if let Some(format) = self.format {
+            args.extend(["--format".into(), format.to_string()]);
+        }
+        if let Some(timeout) = self.timeout_secs {
+            args.extend(["--timeout".into(), timeout.to_string()]);
+        }
+        if let Some(budget) = self.bench_budget_ms {
+            args.extend(["--bench-budget".into(), budget.to_string()]);
+        }
+
+        args
+    }
+}
+
+impl Config {
+    /// Reads `aoc.toml` from the current directory. If not present, returns the default config.
+    ///
+    /// The year falls back to the `AOC_YEAR` environment variable when not configured.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match fs::read_to_string(CONFIG_FILE_PATH) {
+            Ok(s) => s.parse()?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(ConfigError::IO(e)),
+        };
+
+        if config.year.is_none() {
+            config.year = env::var("AOC_YEAR").ok().and_then(|x| x.parse().ok());
+        }
+
+        Ok(config)
+    }
+
+    /// Like [`Config::load`], but prints the error and exits the process on failure.
+    pub fn load_or_exit() -> Self {
+        Self::load().unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    }
+
+    /// Returns a copy of the config with the command-line overrides applied.
+    #[must_use]
+    pub fn with_overrides(mut self, overrides: &ConfigOverrides) -> Self {
+        if let Some(year) = overrides.year {
+            self.year = Some(year);
+        }
+        if let Some(data_dir) = &overrides.data_dir {
+            self.data_dir.clone_from(data_dir);
+        }
+        if let Some(format) = overrides.format {
+            self.format = format;
+        }
+        if let Some(timeout) = overrides.timeout_secs {
+            self.timeout = Some(Duration::from_secs(timeout));
+        }
+        if let Some(budget) = overrides.bench_budget_ms {
+            self.bench.budget = Duration::from_millis(budget);
+        }
+        self
+    }
+
+    /// The configured report sinks. The readme is skipped when `readme.enabled` is false.
+    pub fn report_sinks(&self) -> Vec<Sink> {
+        self.report
+            .sinks
+            .iter()
+            .copied()
+            .filter(|sink| *sink != Sink::Readme || self.readme.enabled)
+            .collect()
+    }
+
+    /// Path of a file for `day` in a data folder, e.g. `data/inputs/01.txt`.
+    pub fn data_file(&self, folder: &str, day: Day, extension: &str) -> PathBuf {
+        self.data_dir
+            .join(folder)
+            .join(format!("{day}.{extension}"))
+    }
+
+    /// Path of a top-level file in the data directory, e.g. `data/timings.json`.
+    pub fn data_path(&self, file: impl AsRef<Path>) -> PathBuf {
+        self.data_dir.join(file)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An error which can be returned when loading the config file.
+#[derive(Debug)]
+pub enum ConfigError {
+    Parser(usize, String),
+    InvalidValue(String, &'static str),
+    UnknownKey(String),
+    IO(io::Error),
+}
+
+impl Error for ConfigError {}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Parser(line, msg) => write!(f, "{CONFIG_FILE_PATH}:{line}: {msg}"),
+            ConfigError::InvalidValue(key, expected) => {
+                write!(f, "{CONFIG_FILE_PATH}: expected `{key}` to be {expected}")
+            }
+            ConfigError::UnknownKey(key) => write!(f, "{CONFIG_FILE_PATH}: unknown key `{key}`"),
+            ConfigError::IO(e) => write!(f, "could not read {CONFIG_FILE_PATH}: {e}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if let Some(inner) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                match chars.next()? {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    '"' => out.push('"'),
+                    '\\' => out.push('\\'),
+                    _ => return None,
+                }
+            } else if c == '"' {
+                return None;
+            } else {
+                out.push(c);
+            }
+        }
+        return Some(Value::String(out));
+    }
+
+    if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return split_array(inner)
+            .into_iter()
+            .map(parse_value)
+            .collect::<Option<_>>()
+            .map(Value::Array);
+    }
+
+    match s {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => {
+            let s = s.replace('_', "");
+            s.parse()
+                .map(Value::Integer)
+                .or_else(|_| s.parse().map(Value::Float))
+                .ok()
+        }
+    }
+}
+
+/// Splits the items of an array on commas that are not part of a string. Allows a trailing comma.
+fn split_array(s: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (idx, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                items.push(s[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(s[start..].trim());
+
+    if items.last() == Some(&"") {
+        items.pop();
+    }
+    items
+}
+
+/// Removes a trailing `#` comment that is not part of a string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parses a document into a flat map keyed by `section.key`.
+fn parse_document(s: &str) -> Result<HashMap<String, Value>, ConfigError> {
+    let mut section = String::new();
+    let mut values = HashMap::new();
+
+    for (idx, line) in s.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| ConfigError::Parser(line_no, "expected `key = value`".into()))?;
+
+        let key = key.trim();
+        let value = parse_value(value.trim())
+            .ok_or_else(|| ConfigError::Parser(line_no, format!("invalid value for `{key}`")))?;
+
+        let full_key = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("{section}.{key}")
+        };
+
+        if values.insert(full_key.clone(), value).is_some() {
+            return Err(ConfigError::Parser(
+                line_no,
+                format!("duplicate key `{full_key}`"),
+            ));
+        }
+    }
+
+    Ok(values)
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+
+        for (key, value) in parse_document(s)? {
+            let invalid = |expected| ConfigError::InvalidValue(key.clone(), expected);
+
+            let as_string = || match &value {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(invalid("a string")),
+            };
+            let as_u64 = || match value {
+                Value::Integer(x) if x >= 0 => Ok(x.unsigned_abs()),
+                _ => Err(invalid("a positive integer")),
+            };
+            let as_bool = || match value {
+                Value::Boolean(x) => Ok(x),
+                _ => Err(invalid("a boolean")),
+            };
+            let as_strings = || match &value {
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(s) => Ok(s.as_str()),
+                        _ => Err(invalid("an array of strings")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|items| items.join(",")),
+                _ => Err(invalid("an array of strings")),
+            };
+
+            match key.as_str() {
+                "year" => {
+                    config.year = Some(as_u64()?.try_into().map_err(|_| invalid("a year"))?);
+                }
+                "data_dir" => config.data_dir = as_string()?.into(),
+                "session_file" => config.session_file = Some(as_string()?.into()),
+                "format" => {
+                    config.format = as_string()?
+                        .parse()
+                        .map_err(|_| invalid("`text` or `json`"))?;
+                }
+                "timeout_secs" => config.timeout = Some(Duration::from_secs(as_u64()?)),
+                "bench.budget_ms" => config.bench.budget = Duration::from_millis(as_u64()?),
+                "bench.min_samples" => config.bench.min_samples = as_u64()?.into(),
+                "bench.max_samples" => config.bench.max_samples = as_u64()?.into(),
+                "bench.count_allocations" => config.bench.count_allocations = as_bool()?,
+                "readme.enabled" => config.readme.enabled = as_bool()?,
+                "readme.stars" => config.readme.stars = as_bool()?,
+                "readme.path" => config.readme.path = as_string()?.into(),
+                "readme.heading" => config.readme.heading = as_string()?,
+                "report.columns" => {
+                    config.report.columns = parse_list(&as_strings()?)
+                        .map_err(|_| invalid("an array of report columns"))?;
+                }
+                "report.sinks" => {
+                    config.report.sinks = parse_list(&as_strings()?)
+                        .map_err(|_| invalid("an array of report sinks"))?;
+                }
+                "report.markdown_path" => config.report.markdown_path = as_string()?.into(),
+                "report.csv_path" => config.report.csv_path = as_string()?.into(),
+                "report.html_path" => config.report.html_path = as_string()?.into(),
+                _ => return Err(ConfigError::UnknownKey(key)),
+            }
+        }
+
+        if config.bench.min_samples == 0 || config.bench.min_samples > config.bench.max_samples {
+            return Err(ConfigError::InvalidValue(
+                "bench.min_samples".into(),
+                "between 1 and `bench.max_samples`",
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::{Config, ConfigOverrides, OutputFormat};
+    use crate::template::benchmark_report::{Column, Sink};
+
+    #[test]
+    fn parses_empty_config() {
+        let config: Config = "# nothing here\n".parse().unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn parses_full_config() {
+        let config: Config = r####"
+            year = 2024
+            data_dir = "puzzles" # trailing comment
+            session_file = "~/.aoc#session"
+            format = "json"
+            timeout_secs = 30
+
+            [bench]
+            budget_ms = 500
+            min_samples = 5
+            max_samples = 1_000
+            count_allocations = true
+
+            [readme]
+            enabled = false
+            stars = false
+            heading = "### Timings"
+
+            [report]
+            columns = ["part_1", "median", "chart",]
+            sinks = ["readme", "csv"]
+            csv_path = "data/benchmarks.csv"
+        "####
+            .parse()
+            .unwrap();
+
+        assert_eq!(config.year, Some(2024));
+        assert_eq!(config.data_dir, PathBuf::from("puzzles"));
+        assert_eq!(config.session_file, Some(PathBuf::from("~/.aoc#session")));
+        assert_eq!(config.format, OutputFormat::Json);
+        assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(config.bench.budget, Duration::from_millis(500));
+        assert_eq!(config.bench.min_samples, 5);
+        assert_eq!(config.bench.max_samples, 1000);
+        assert_eq!(config.bench.count_allocations, true);
+        assert_eq!(config.readme.enabled, false);
+        assert_eq!(config.readme.stars, false);
+        assert_eq!(config.readme.path, PathBuf::from("README.md"));
+        assert_eq!(config.readme.heading, "### Timings");
+        assert_eq!(
+            config.report.columns,
+            vec![Column::Part1, Column::Median, Column::Chart]
+        );
+        assert_eq!(config.report.sinks, vec![Sink::Readme, Sink::Csv]);
+        assert_eq!(config.report.csv_path, PathBuf::from("data/benchmarks.csv"));
+        assert_eq!(config.report.markdown_path, PathBuf::from("BENCHMARKS.md"));
+        // the readme is disabled, so only the csv sink remains.
+        assert_eq!(config.report_sinks(), vec![Sink::Csv]);
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!("year = \"2024\"".parse::<Config>().is_err());
+        assert!("foo = 1".parse::<Config>().is_err());
+        assert!("year 2024".parse::<Config>().is_err());
+        assert!("year = 2024\nyear = 2023".parse::<Config>().is_err());
+        assert!("[bench]\nmin_samples = 0".parse::<Config>().is_err());
+        assert!("format = \"xml\"".parse::<Config>().is_err());
+        assert!("[report]\ncolumns = [\"part_3\"]"
+            .parse::<Config>()
+            .is_err());
+        assert!("[report]\nsinks = \"readme\"".parse::<Config>().is_err());
+        assert!("[report]\nsinks = [\"readme\"".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn applies_overrides() {
+        let config: Config = "year = 2023\ntimeout_secs = 10".parse().unwrap();
+        let overrides = ConfigOverrides {
+            year: Some(2024),
+            data_dir: None,
+            format: Some(OutputFormat::Json),
+            timeout_secs: None,
+            bench_budget_ms: Some(100),
+        };
+        let config = config.with_overrides(&overrides);
+        assert_eq!(config.year, Some(2024));
+        assert_eq!(config.format, OutputFormat::Json);
+        assert_eq!(config.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(config.bench.budget, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn forwards_overrides() {
+        let overrides = ConfigOverrides {
+            year: Some(2024),
+            timeout_secs: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(overrides.to_args(), ["--year", "2024", "--timeout", "5"]);
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::str::FromStr;
+
+use crate::template::config::Config;
+use crate::template::run_multi::get_path_for_bin;
+use crate::template::timings::Timings;
+use crate::template::{all_days, Day};
+
+/// A single term of a [`DaySelection`] expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DayFilter {
+    /// A single day, e.g. `7`.
+    Day(Day),
+    /// An inclusive range of days, e.g. `1-5`.
+    Range(Day, Day),
+    /// Every day of advent.
+    All,
+    /// Every odd day.
+    Odd,
+    /// Every even day.
+    Even,
+    /// Days that do not have a stored timing for both parts.
+    Unsolved,
+    /// The `n` days with the highest stored total run time.
+    Slowest(usize),
+    /// Days whose solution declares the given tag (see [`parse_tags`]).
+    Tag(String),
+}
+
+/// A set of days described by a comma-separated list of [`DayFilter`] terms.
+///
+/// The selected days are the union of all terms, e.g. `1-5,odd,tag:grid`.
+///
+/// ```
+/// # use advent_of_code::template::DaySelection;
+/// let selection: DaySelection = "1-3,7".parse().unwrap();
+/// assert_eq!(selection.to_string(), "1-3,7");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaySelection {
+    filters: Vec<DayFilter>,
+}
+
+impl DaySelection {
+    /// Creates a selection that contains every day of advent.
+    pub fn all() -> Self {
+        Self::from(DayFilter::All)
+    }
+
+    /// Resolves the selection against the stored timings and solution sources.
+    pub fn resolve(&self, config: &Config) -> HashSet<Day> {
+        self.resolve_with(&Timings::read_from_file(config))
+    }
+
+    /// Resolves the selection against the provided timings.
+    pub(crate) fn resolve_with(&self, timings: &Timings) -> HashSet<Day> {
+        self.resolve_using(timings, read_tags)
+    }
+
+    fn resolve_using(
+        &self,
+        timings: &Timings,
+        tags_for: impl Fn(Day) -> Vec<String>,
+    ) -> HashSet<Day> {
+        let mut days = HashSet::new();
+
+        for filter in &self.filters {
+            match filter {
+                DayFilter::Day(day) => {
+                    days.insert(*day);
+                }
+                DayFilter::Range(start, end) => {
+                    days.extend(all_days().filter(|day| day >= start && day <= end));
+                }
+                DayFilter::All => days.extend(all_days()),
+                DayFilter::Odd => days.extend(all_days().filter(|day| day.into_inner() % 2 == 1)),
+                DayFilter::Even => days.extend(all_days().filter(|day| day.into_inner() % 2 == 0)),
+                DayFilter::Unsolved => {
+                    days.extend(all_days().filter(|day| !timings.is_day_complete(*day)));
+                }
+                DayFilter::Slowest(count) => {
+                    let mut sorted = timings.data.iter().collect::<Vec<_>>();
+                    sorted.sort_unstable_by(|a, b| b.total_nanos.total_cmp(&a.total_nanos));
+                    days.extend(sorted.iter().take(*count).map(|timing| timing.day));
+                }
+                DayFilter::Tag(tag) => {
+                    days.extend(all_days().filter(|day| tags_for(*day).contains(tag)));
+                }
+            }
+        }
+
+        days
+    }
+}
+
+impl From<DayFilter> for DaySelection {
+    fn from(filter: DayFilter) -> Self {
+        Self {
+            filters: vec![filter],
+        }
+    }
+}
+
+impl From<Day> for DaySelection {
+    fn from(day: Day) -> Self {
+        Self::from(DayFilter::Day(day))
+    }
+}
+
+/// Reads the tags declared by the solution of `day`. Returns an empty list for unscaffolded days.
+fn read_tags(day: Day) -> Vec<String> {
+    fs::read_to_string(get_path_for_bin(day))
+        .map(|source| parse_tags(&source))
+        .unwrap_or_default()
+}
+
+/// Parses tags from the leading `//! tags: a, b` inner doc comment of a solution file.
+pub fn parse_tags(source: &str) -> Vec<String> {
+    source
+        .lines()
+        .take_while(|line| line.starts_with("//!"))
+        .filter_map(|line| line.trim_start_matches("//!").trim().strip_prefix("tags:"))
+        .flat_map(|tags| tags.split(','))
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl FromStr for DayFilter {
+    type Err = DaySelectionFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || DaySelectionFromStrError(s.to_string());
+
+        let filter = match s {
+            "all" => DayFilter::All,
+            "odd" => DayFilter::Odd,
+            "even" => DayFilter::Even,
+            "unsolved" => DayFilter::Unsolved,
+            _ => {
+                if let Some(count) = s.strip_prefix("slowest:") {
+                    DayFilter::Slowest(count.parse().map_err(|_| err())?)
+                } else if let Some(tag) = s.strip_prefix("tag:") {
+                    if tag.is_empty() {
+                        return Err(err());
+                    }
+                    DayFilter::Tag(tag.to_lowercase())
+                } else if let Some((start, end)) = s.split_once('-') {
+                    let start: Day = start.parse().map_err(|_| err())?;
+                    let end: Day = end.parse().map_err(|_| err())?;
+                    if start > end {
+                        return Err(err());
+                    }
+                    DayFilter::Range(start, end)
+                } else {
+                    DayFilter::Day(s.parse().map_err(|_| err())?)
+                }
+            }
+        };
+
+        Ok(filter)
+    }
+}
+
+impl FromStr for DaySelection {
+    type Err = DaySelectionFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let filters = s
+            .split(',')
+            .map(|term| term.trim().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { filters })
+    }
+}
+
+impl Display for DayFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DayFilter::Day(day) => write!(f, "{}", day.into_inner()),
+            DayFilter::Range(start, end) => {
+                write!(f, "{}-{}", start.into_inner(), end.into_inner())
+            }
+            DayFilter::All => f.write_str("all"),
+            DayFilter::Odd => f.write_str("odd"),
+            DayFilter::Even => f.write_str("even"),
+            DayFilter::Unsolved => f.write_str("unsolved"),
+            DayFilter::Slowest(count) => write!(f, "slowest:{count}"),
+            DayFilter::Tag(tag) => write!(f, "tag:{tag}"),
+        }
+    }
+}
+
+impl Display for DaySelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let terms = self
+            .filters
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        f.write_str(&terms.join(","))
+    }
+}
+
+/// An error which can be returned when parsing a [`DaySelection`].
+#[derive(Debug)]
+pub struct DaySelectionFromStrError(String);
+
+impl Error for DaySelectionFromStrError {}
+
+impl Display for DaySelectionFromStrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid day selection `{}`, expecting a day, a range (`1-5`), \
+            `all`, `odd`, `even`, `unsolved`, `slowest:<n>` or `tag:<name>`",
+            self.0
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{parse_tags, DaySelection};
+    use crate::day;
+    use crate::template::timings::{Timing, Timings};
+    use crate::template::Day;
+
+    fn get_mock_timings() -> Timings {
+        Timings {
+            data: vec![
+                Timing {
+                    day: day!(1),
+                    part_1: Some("10ms".into()),
+                    part_2: Some("20ms".into()),
+                    total_nanos: 3e+7,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
+                },
+                Timing {
+                    day: day!(2),
+                    part_1: Some("30ms".into()),
+                    part_2: Some("40ms".into()),
+                    total_nanos: 7e+7,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
+                },
+                Timing {
+                    day: day!(4),
+                    part_1: Some("40ms".into()),
+                    part_2: None,
+                    total_nanos: 4e+7,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
+                },
+            ],
+        }
+    }
+
+    fn resolve(s: &str) -> Vec<u8> {
+        let selection: DaySelection = s.parse().unwrap();
+        let tags_for = |day: Day| {
+            if day == 6 || day == 12 {
+                vec!["grid".to_string()]
+            } else {
+                vec![]
+            }
+        };
+        let days: HashSet<Day> = selection.resolve_using(&get_mock_timings(), tags_for);
+        let mut days = days.into_iter().map(Day::into_inner).collect::<Vec<_>>();
+        days.sort_unstable();
+        days
+    }
+
+    #[test]
+    fn resolves_days_and_ranges() {
+        assert_eq!(resolve("7"), vec![7]);
+        assert_eq!(resolve("1-5"), vec![1, 2, 3, 4, 5]);
+        assert_eq!(resolve("3,7,12"), vec![3, 7, 12]);
+        assert_eq!(resolve("1-2, 2-3"), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn resolves_keywords() {
+        assert_eq!(resolve("all").len(), 25);
+        assert_eq!(resolve("odd").len(), 13);
+        assert_eq!(resolve("even").len(), 12);
+        assert_eq!(resolve("unsolved").contains(&1), false);
+        assert_eq!(resolve("unsolved").contains(&4), true);
+    }
+
+    #[test]
+    fn resolves_slowest() {
+        assert_eq!(resolve("slowest:1"), vec![2]);
+        assert_eq!(resolve("slowest:2"), vec![2, 4]);
+        assert_eq!(resolve("slowest:10"), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn resolves_tags() {
+        assert_eq!(resolve("tag:grid"), vec![6, 12]);
+        assert_eq!(resolve("tag:grid,1"), vec![1, 6, 12]);
+        assert_eq!(resolve("tag:none"), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert!("".parse::<DaySelection>().is_err());
+        assert!("26".parse::<DaySelection>().is_err());
+        assert!("5-1".parse::<DaySelection>().is_err());
+        assert!("slowest:x".parse::<DaySelection>().is_err());
+        assert!("tag:".parse::<DaySelection>().is_err());
+        assert!("1,,2".parse::<DaySelection>().is_err());
+    }
+
+    #[test]
+    fn displays_expressions() {
+        let selection: DaySelection = "1-5, odd,slowest:3,tag:Grid".parse().unwrap();
+        assert_eq!(selection.to_string(), "1-5,odd,slowest:3,tag:grid");
+    }
+
+    #[test]
+    fn parses_tags() {
+        let source =
+            "//! tags: grid, Simulation\n//!\nadvent_of_code::solution!(6);\n// tags: ignored";
+        assert_eq!(parse_tags(source), vec!["grid", "simulation"]);
+        assert_eq!(
+            parse_tags("advent_of_code::solution!(1);"),
+            Vec::<String>::new()
+        );
+    }
+}
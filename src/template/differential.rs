@@ -0,0 +1,427 @@
+/// Differential testing of alternative implementations. Random inputs are generated with a per-day
+/// generator, a reference (e.g. brute-force) implementation and an optimized one are run on each
+/// input, and the first input they disagree on is shrunk to a minimal example.
+use std::env;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Seed used when `AOC_CHECK_SEED` is not set, so test runs are reproducible.
+const DEFAULT_SEED: u64 = 0x5EED_0FAD_7E27;
+const DEFAULT_CASES: u32 = 100;
+const DEFAULT_MAX_SHRINKS: u32 = 1000;
+
+/// A small, deterministic random number generator (SplitMix64) for input generators.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in the inclusive `range`.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "cannot sample from an empty range");
+        let span = end.wrapping_sub(start) as u64;
+        match span.checked_add(1) {
+            Some(len) => start.wrapping_add((self.next_u64() % len) as i64),
+            None => self.next_u64() as i64,
+        }
+    }
+
+    /// Returns an index in `0..len`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "cannot pick an index of an empty collection");
+        (self.next_u64() % len as u64) as usize
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Picks a random element of `items`.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    /// Generates a vector with a random length in `len`.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    pub fn vec<T>(
+        &mut self,
+        len: RangeInclusive<usize>,
+        mut generate: impl FnMut(&mut Rng) -> T,
+    ) -> Vec<T> {
+        let len = self.range(*len.start() as i64..=*len.end() as i64) as usize;
+        (0..len).map(|_| generate(self)).collect()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Values that can be simplified when looking for a minimal failing input.
+pub trait Shrink: Sized {
+    /// Returns simpler candidates of `self`, simplest first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! impl_shrink_int {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                /// Integers shrink towards zero.
+                #[allow(unused_comparisons)]
+                fn shrink(&self) -> Vec<Self> {
+                    let value = *self;
+                    let mut candidates: Vec<Self> = vec![];
+                    if value == 0 {
+                        return candidates;
+                    }
+                    candidates.push(0);
+                    if value < 0 {
+                        candidates.push(value.wrapping_neg());
+                    }
+                    let mut delta = value / 2;
+                    while delta != 0 {
+                        candidates.push(value - delta);
+                        delta /= 2;
+                    }
+                    candidates.push(if value > 0 { value - 1 } else { value + 1 });
+                    candidates.dedup();
+                    candidates
+                }
+            }
+        )*
+    };
+}
+
+impl_shrink_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self {
+            vec![false]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl Shrink for char {
+    fn shrink(&self) -> Vec<Self> {
+        if *self == 'a' {
+            vec![]
+        } else {
+            vec!['a']
+        }
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Option<T> {
+    fn shrink(&self) -> Vec<Self> {
+        match self {
+            None => vec![],
+            Some(value) => std::iter::once(None)
+                .chain(value.shrink().into_iter().map(Some))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    /// Vectors shrink by dropping chunks of elements, then by shrinking single elements.
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = vec![];
+
+        let mut chunk = self.len() / 2;
+        while chunk > 0 {
+            for start in (0..self.len()).step_by(chunk) {
+                let mut candidate = self[..start].to_vec();
+                candidate.extend_from_slice(&self[(start + chunk).min(self.len())..]);
+                candidates.push(candidate);
+            }
+            chunk /= 2;
+        }
+
+        if self.len() == 1 {
+            candidates.push(vec![]);
+        }
+
+        for (idx, value) in self.iter().enumerate() {
+            for shrunk in value.shrink() {
+                let mut candidate = self.clone();
+                candidate[idx] = shrunk;
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+}
+
+macro_rules! impl_shrink_tuple {
+    ($(($($name:ident $idx:tt),+))*) => {
+        $(
+            impl<$($name: Shrink + Clone),+> Shrink for ($($name,)+) {
+                fn shrink(&self) -> Vec<Self> {
+                    let mut candidates = vec![];
+                    $(
+                        for shrunk in self.$idx.shrink() {
+                            let mut candidate = self.clone();
+                            candidate.$idx = shrunk;
+                            candidates.push(candidate);
+                        }
+                    )+
+                    candidates
+                }
+            }
+        )*
+    };
+}
+
+impl_shrink_tuple! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A differential check of two implementations on generated inputs.
+///
+/// ```
+/// use advent_of_code::template::differential::Differential;
+///
+/// Differential::new(|rng| rng.vec(0..=20, |rng| rng.range(0..=100)))
+///     .check(
+///         |xs| xs.iter().max().copied(),
+///         |xs| xs.iter().copied().reduce(i64::max),
+///     );
+/// ```
+pub struct Differential<'a, T> {
+    generate: Box<dyn Fn(&mut Rng) -> T + 'a>,
+    assume: Box<dyn Fn(&T) -> bool + 'a>,
+    cases: u32,
+    seed: u64,
+    max_shrinks: u32,
+}
+
+impl<'a, T: Shrink + Clone + Debug> Differential<'a, T> {
+    /// Creates a check of inputs produced by `generate`.
+    /// The seed can be overridden with the `AOC_CHECK_SEED` environment variable.
+    pub fn new(generate: impl Fn(&mut Rng) -> T + 'a) -> Self {
+        Self {
+            generate: Box::new(generate),
+            assume: Box::new(|_| true),
+            cases: DEFAULT_CASES,
+            seed: env::var("AOC_CHECK_SEED")
+                .ok()
+                .and_then(|seed| seed.parse().ok())
+                .unwrap_or(DEFAULT_SEED),
+            max_shrinks: DEFAULT_MAX_SHRINKS,
+        }
+    }
+
+    /// Restricts inputs, including shrunk ones, to those that satisfy `assume`.
+    #[must_use]
+    pub fn assume(mut self, assume: impl Fn(&T) -> bool + 'a) -> Self {
+        self.assume = Box::new(assume);
+        self
+    }
+
+    /// Sets the number of generated inputs.
+    #[must_use]
+    pub fn cases(mut self, cases: u32) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs both implementations on every generated input.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the shrunk input if the implementations disagree or one of them panics.
+    pub fn check<R: PartialEq + Debug>(
+        &self,
+        reference: impl Fn(&T) -> R,
+        optimized: impl Fn(&T) -> R,
+    ) {
+        if let Err(failure) = self.run(reference, optimized) {
+            panic!("{failure}");
+        }
+    }
+
+    /// Like [`Differential::check`], but returns a description of the failure instead of panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the shrunk failing input if the implementations disagree.
+    pub fn run<R: PartialEq + Debug>(
+        &self,
+        reference: impl Fn(&T) -> R,
+        optimized: impl Fn(&T) -> R,
+    ) -> Result<(), String> {
+        let mut rng = Rng::new(self.seed);
+        let disagrees = |input: &T| {
+            let expected = catch(|| reference(input));
+            let actual = catch(|| optimized(input));
+            (expected != actual).then_some((expected, actual))
+        };
+
+        for case in 1..=self.cases {
+            let input = (self.generate)(&mut rng);
+            if !(self.assume)(&input) {
+                continue;
+            }
+
+            let Some(outcome) = disagrees(&input) else {
+                continue;
+            };
+
+            let (input, (expected, actual), steps) = self.shrink(input, outcome, disagrees);
+
+            return Err(format!(
+                "implementations disagree on case {case} (seed {}, shrunk {steps} times)\n  input:     {input:?}\n  reference: {}\n  optimized: {}",
+                self.seed,
+                format_outcome(&expected),
+                format_outcome(&actual),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Greedily replaces the failing input with its first simpler candidate that still fails.
+    fn shrink<O>(
+        &self,
+        mut input: T,
+        mut outcome: O,
+        disagrees: impl Fn(&T) -> Option<O>,
+    ) -> (T, O, u32) {
+        let mut steps = 0;
+
+        'shrinking: while steps < self.max_shrinks {
+            for candidate in input.shrink() {
+                if !(self.assume)(&candidate) {
+                    continue;
+                }
+                if let Some(candidate_outcome) = disagrees(&candidate) {
+                    input = candidate;
+                    outcome = candidate_outcome;
+                    steps += 1;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+
+        (input, outcome, steps)
+    }
+}
+
+/// Runs `f`, turning a panic into an error with the panic message.
+fn catch<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|e| {
+        e.downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panicked".into())
+    })
+}
+
+fn format_outcome<R: Debug>(outcome: &Result<R, String>) -> String {
+    match outcome {
+        Ok(value) => format!("{value:?}"),
+        Err(message) => format!("panicked: {message}"),
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Differential, Rng, Shrink};
+
+    #[test]
+    fn generates_in_range() {
+        let mut rng = Rng::new(42);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.range(-3..=3)));
+        }
+        assert_eq!(rng.range(5..=5), 5);
+
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        assert_eq!(a.vec(0..=10, Rng::next_u64), b.vec(0..=10, Rng::next_u64));
+    }
+
+    #[test]
+    fn shrinks_integers_towards_zero() {
+        assert_eq!(8_u32.shrink(), vec![0, 4, 6, 7]);
+        assert_eq!((-4_i32).shrink(), vec![0, 4, -2, -3]);
+        assert!(0_u8.shrink().is_empty());
+    }
+
+    #[test]
+    fn shrinks_vectors() {
+        let candidates = vec![1_u8, 2].shrink();
+        assert_eq!(candidates[..2], [vec![2], vec![1]]);
+        assert!(candidates.contains(&vec![0, 2]));
+        assert_eq!(vec![3_u8].shrink()[0], vec![]);
+    }
+
+    #[test]
+    fn passes_agreeing_implementations() {
+        Differential::new(|rng| rng.vec(0..=20, |rng| rng.range(-100..=100))).check(
+            |xs| xs.iter().sum::<i64>(),
+            |xs| xs.iter().rev().sum::<i64>(),
+        );
+    }
+
+    #[test]
+    fn shrinks_disagreements_to_minimal_inputs() {
+        // the "optimized" sum ignores all values above 50.
+        let failure = Differential::new(|rng| rng.vec(0..=20, |rng| rng.range(0..=100)))
+            .run(
+                |xs| xs.iter().sum::<i64>(),
+                |xs| xs.iter().filter(|x| **x <= 50).sum::<i64>(),
+            )
+            .unwrap_err();
+
+        assert!(failure.contains("input:     [51]"), "{failure}");
+        assert!(failure.contains("reference: 51"), "{failure}");
+        assert!(failure.contains("optimized: 0"), "{failure}");
+    }
+
+    #[test]
+    fn reports_panics_and_respects_assumptions() {
+        let failure = Differential::new(|rng| rng.vec(1..=10, |rng| rng.range(1..=9)))
+            .assume(|xs| !xs.is_empty())
+            .run(|xs| xs[0], |xs| xs[0] + (xs[0] - 1) / (xs[0] - 1) - 1)
+            .unwrap_err();
+
+        assert!(failure.contains("input:     [1]"), "{failure}");
+        assert!(failure.contains("optimized: panicked"), "{failure}");
+    }
+}
@@ -0,0 +1,381 @@
+/// Append-only benchmark history. Every `time --store` appends one line per benched day to
+/// `timings-history.jsonl`, together with the environment the benchmark ran in.
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::Command;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tinyjson::JsonValue;
+
+use crate::template::config::Config;
+use crate::template::timings::{Timing, Timings};
+use crate::template::{Day, ANSI_BOLD, ANSI_RESET};
+
+static HISTORY_FILE_NAME: &str = "timings-history.jsonl";
+
+/// The environment a benchmark ran in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    /// Short hash of the `HEAD` commit, suffixed with `-dirty` for uncommitted changes.
+    pub commit: Option<String>,
+    /// Output of `rustc --version`.
+    pub rustc: Option<String>,
+    pub cpu: Option<String>,
+}
+
+/// A single benchmark of a day.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub environment: Environment,
+    pub timing: Timing,
+}
+
+impl Environment {
+    /// Collects the current environment. Values that cannot be determined are left empty.
+    ///
+    /// Changes to the data directory, e.g. the stored timings and this history, do not make the
+    /// commit dirty. Neither do untracked files.
+    pub fn current(config: &Config) -> Self {
+        let commit = command_output("git", &["rev-parse", "--short", "HEAD"]).map(|commit| {
+            let mut args = vec![
+                "status".to_string(),
+                "--porcelain".into(),
+                "--untracked-files=no".into(),
+                "--".into(),
+                ".".into(),
+            ];
+            // a data directory outside of the repository is not seen by git anyway.
+            if config.data_dir.is_relative() {
+                args.push(format!(":(exclude){}", config.data_dir.display()));
+            }
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+            let is_dirty = command_output("git", &args).is_some();
+            if is_dirty {
+                format!("{commit}-dirty")
+            } else {
+                commit
+            }
+        });
+
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            commit,
+            rustc: command_output("rustc", &["--version"]),
+            cpu: cpu_model(),
+        }
+    }
+}
+
+/// Runs a command and returns its trimmed stdout, or `None` if it failed or printed nothing.
+fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(cmd).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+fn cpu_model() -> Option<String> {
+    if cfg!(target_os = "macos") {
+        return command_output("sysctl", &["-n", "machdep.cpu.brand_string"]);
+    }
+
+    fs::read_to_string("/proc/cpuinfo")
+        .ok()?
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+/// Appends the timings to the history file in the configured data directory.
+pub fn append(timings: &Timings, environment: &Environment, config: &Config) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(config.data_path(HISTORY_FILE_NAME))?;
+
+    for timing in &timings.data {
+        let entry = HistoryEntry {
+            environment: environment.clone(),
+            timing: timing.clone(),
+        };
+        let line = JsonValue::from(&entry)
+            .stringify()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        writeln!(file, "{line}")?;
+    }
+
+    Ok(())
+}
+
+/// Reads all history entries of `day`, oldest first. Lines that cannot be parsed are skipped.
+pub fn read(day: Day, config: &Config) -> Vec<HistoryEntry> {
+    let Ok(content) = fs::read_to_string(config.data_path(HISTORY_FILE_NAME)) else {
+        return vec![];
+    };
+
+    parse(&content)
+        .into_iter()
+        .filter(|entry| entry.timing.day == day)
+        .collect()
+}
+
+fn parse(content: &str) -> Vec<HistoryEntry> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(idx, line)| {
+            let entry = JsonValue::from_str(line)
+                .map_err(|_| "not valid JSON.".to_string())
+                .and_then(|json| HistoryEntry::try_from(&json));
+            match entry {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    eprintln!("Skipping line {} of {HISTORY_FILE_NAME}: {e}", idx + 1);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Renders the history of a day as a table, with the change of the total time to the previous entry.
+pub fn render(day: Day, entries: &[HistoryEntry]) -> String {
+    let mut rows = vec![[
+        "Date", "Commit", "Rustc", "Part 1", "Part 2", "Total", "Change", "CPU",
+    ]
+    .map(String::from)];
+
+    let mut previous: Option<f64> = None;
+
+    for entry in entries {
+        let Environment {
+            timestamp,
+            commit,
+            rustc,
+            cpu,
+        } = &entry.environment;
+        let timing = &entry.timing;
+
+        let change = match previous {
+            Some(previous) if previous > 0.0 => {
+                format!("{:+.1}%", (timing.total_nanos / previous - 1.0) * 100.0)
+            }
+            _ => "-".into(),
+        };
+        previous = Some(timing.total_nanos);
+
+        let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".into());
+
+        rows.push([
+            format_timestamp(*timestamp),
+            or_dash(commit),
+            // `rustc 1.83.0 (90b35a623 2024-11-26)` -> `1.83.0`
+            or_dash(
+                &rustc
+                    .as_ref()
+                    .and_then(|r| r.split(' ').nth(1))
+                    .map(String::from),
+            ),
+            or_dash(&timing.part_1),
+            or_dash(&timing.part_2),
+            format_nanos(timing.total_nanos),
+            change,
+            or_dash(cpu),
+        ]);
+    }
+
+    let widths = (0..rows[0].len())
+        .map(|col| {
+            rows.iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![format!("{ANSI_BOLD}Day {day} history{ANSI_RESET}")];
+
+    for row in &rows {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>();
+        lines.push(cells.join("  ").trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_nanos(nanos: f64) -> String {
+    format!("{:.1?}", Duration::from_nanos(nanos as u64))
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM` (UTC).
+fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let seconds = timestamp % 86_400;
+
+    // convert days since the epoch to a civil date, see: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&HistoryEntry> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &HistoryEntry) -> Self {
+        let JsonValue::Object(mut map) = JsonValue::from(&value.timing) else {
+            unreachable!("timings serialize to an object");
+        };
+
+        let environment = &value.environment;
+
+        map.insert(
+            "timestamp".into(),
+            JsonValue::Number(environment.timestamp as f64),
+        );
+
+        for (key, value) in [
+            ("commit", &environment.commit),
+            ("rustc", &environment.rustc),
+            ("cpu", &environment.cpu),
+        ] {
+            map.insert(
+                key.into(),
+                value.clone().map_or(JsonValue::Null, JsonValue::String),
+            );
+        }
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for HistoryEntry {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let timing = Timing::try_from(value)?;
+
+        let json: &HashMap<String, JsonValue> = value
+            .get()
+            .ok_or("Expected history entry to be a JSON object.")?;
+
+        let timestamp = json
+            .get("timestamp")
+            .and_then(|v| v.get::<f64>().copied())
+            .ok_or("Expected entry.timestamp to be a number.")? as u64;
+
+        let get_string = |key: &str| json.get(key).and_then(|v| v.get::<String>()).cloned();
+
+        Ok(HistoryEntry {
+            environment: Environment {
+                timestamp,
+                commit: get_string("commit"),
+                rustc: get_string("rustc"),
+                cpu: get_string("cpu"),
+            },
+            timing,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use tinyjson::JsonValue;
+
+    use super::{format_timestamp, parse, render, Environment, HistoryEntry};
+    use crate::day;
+    use crate::template::timings::Timing;
+
+    fn get_mock_entry(timestamp: u64, commit: &str, total_nanos: f64) -> HistoryEntry {
+        HistoryEntry {
+            environment: Environment {
+                timestamp,
+                commit: Some(commit.into()),
+                rustc: Some("rustc 1.83.0 (90b35a623 2024-11-26)".into()),
+                cpu: None,
+            },
+            timing: Timing {
+                day: day!(1),
+                part_1: Some("10.0ms".into()),
+                part_2: None,
+                total_nanos,
+                part_1_heap: None,
+                part_2_heap: None,
+                median_nanos: None,
+                min_nanos: None,
+                samples: Some(100),
+            },
+        }
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_733_011_200), "2024-12-01 00:00");
+        assert_eq!(format_timestamp(1_709_210_096), "2024-02-29 12:34");
+    }
+
+    #[test]
+    fn round_trips_entries() {
+        let entry = get_mock_entry(1_733_011_200, "abc1234", 1e+7);
+        let line = JsonValue::from(&entry).stringify().unwrap();
+        assert!(!line.contains('\n'));
+
+        let parsed = parse(&format!("{line}\n\nnot json\n{line}\n"));
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].environment, entry.environment);
+        assert_eq!(parsed[0].timing.part_1, Some("10.0ms".into()));
+        assert_eq!(parsed[0].timing.samples, Some(100));
+    }
+
+    #[test]
+    fn renders_changes() {
+        let entries = [
+            get_mock_entry(1_733_011_200, "abc1234", 2e+7),
+            get_mock_entry(1_733_097_600, "def5678", 1e+7),
+        ];
+        let table = render(day!(1), &entries);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1]
+            .starts_with("Date              Commit   Rustc   Part 1  Part 2  Total   Change  CPU"));
+        assert!(lines[2]
+            .starts_with("2024-12-01 00:00  abc1234  1.83.0  10.0ms  -       20.0ms  -       -"));
+        assert!(lines[3].contains("def5678"));
+        assert!(lines[3].contains("10.0ms  -50.0%"));
+    }
+}
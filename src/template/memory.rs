@@ -0,0 +1,299 @@
+/// Heap statistics of solution parts, collected when running with the `dhat-heap` feature.
+/// Allocation counts of benchmarks, collected when running with the `count-alloc` feature.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tinyjson::JsonValue;
+
+/// Heap usage of a single solution part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapSummary {
+    /// Bytes allocated over the whole run.
+    pub total_bytes: u64,
+    /// Bytes allocated at the global peak.
+    pub peak_bytes: u64,
+    /// Number of allocations over the whole run.
+    pub allocations: u64,
+}
+
+/// Reads the statistics of the running `dhat` heap profiler.
+#[cfg(feature = "dhat-heap")]
+pub fn current_heap_summary() -> Option<HeapSummary> {
+    let stats = dhat::HeapStats::get();
+    Some(HeapSummary {
+        total_bytes: stats.total_bytes,
+        peak_bytes: stats.max_bytes as u64,
+        allocations: stats.total_blocks,
+    })
+}
+
+/// Heap statistics are only available with the `dhat-heap` feature.
+#[cfg(not(feature = "dhat-heap"))]
+pub fn current_heap_summary() -> Option<HeapSummary> {
+    None
+}
+
+/// A global allocator that counts allocations and allocated bytes on top of the system allocator.
+/// Installed by the `solution!` macro when the `count-alloc` feature is enabled.
+pub struct CountingAlloc;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+fn record(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // count a reallocation like dhat does: as a new block of `new_size` bytes.
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+/// Allocations made by a benched solution part, averaged over all samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocCounts {
+    pub allocations: u64,
+    pub bytes: u64,
+}
+
+impl AllocCounts {
+    /// Reads the counters of [`CountingAlloc`].
+    /// Returns `None` if the `count-alloc` feature is disabled or `dhat-heap` owns the global allocator.
+    pub fn current() -> Option<Self> {
+        if !cfg!(feature = "count-alloc") || cfg!(feature = "dhat-heap") {
+            return None;
+        }
+
+        Some(Self {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Averages the difference between two counter readings over `samples` runs.
+    pub fn per_iteration(start: Self, end: Self, samples: u128) -> Self {
+        let samples = u64::try_from(samples).unwrap_or(u64::MAX).max(1);
+        Self {
+            allocations: (end.allocations - start.allocations) / samples,
+            bytes: (end.bytes - start.bytes) / samples,
+        }
+    }
+}
+
+impl Display for AllocCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[alloc: {} allocs/iter, {}/iter]",
+            self.allocations,
+            format_bytes(self.bytes)
+        )
+    }
+}
+
+static UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+/// Formats a byte count with a binary unit, e.g. `1.5 KiB`.
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Parses a byte count formatted by [`format_bytes`]. The result is rounded to the displayed precision.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_wrap
+)]
+pub fn parse_bytes(s: &str) -> Option<u64> {
+    let (value, unit) = s.trim().split_once(' ')?;
+    let exponent = UNITS.iter().position(|u| *u == unit)?;
+    let value: f64 = value.parse().ok()?;
+    Some((value * 1024_f64.powi(exponent as i32)).round() as u64)
+}
+
+impl HeapSummary {
+    /// Parses the summary from a line of solution output, e.g.
+    /// `Part 1: 42 (4.1ms) [heap: 1.2 KiB total, 232 B peak, 3 allocs]`.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let summary = line.rsplit_once("[heap: ")?.1.strip_suffix(']')?;
+        let mut parts = summary.split(", ");
+
+        let total_bytes = parse_bytes(parts.next()?.strip_suffix(" total")?)?;
+        let peak_bytes = parse_bytes(parts.next()?.strip_suffix(" peak")?)?;
+        let allocations = parts.next()?.strip_suffix(" allocs")?.parse().ok()?;
+
+        Some(Self {
+            total_bytes,
+            peak_bytes,
+            allocations,
+        })
+    }
+}
+
+impl Display for HeapSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[heap: {} total, {} peak, {} allocs]",
+            format_bytes(self.total_bytes),
+            format_bytes(self.peak_bytes),
+            self.allocations
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&HeapSummary> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &HeapSummary) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert(
+            "total_bytes".into(),
+            JsonValue::Number(value.total_bytes as f64),
+        );
+        map.insert(
+            "peak_bytes".into(),
+            JsonValue::Number(value.peak_bytes as f64),
+        );
+        map.insert(
+            "allocations".into(),
+            JsonValue::Number(value.allocations as f64),
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for HeapSummary {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected heap summary to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .map(|v| v as u64)
+                .ok_or(format!("Expected heap.{key} to be a number."))
+        };
+
+        Ok(HeapSummary {
+            total_bytes: get_number("total_bytes")?,
+            peak_bytes: get_number("peak_bytes")?,
+            allocations: get_number("allocations")?,
+        })
+    }
+}
+
+impl From<&AllocCounts> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &AllocCounts) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert(
+            "allocations".into(),
+            JsonValue::Number(value.allocations as f64),
+        );
+        map.insert("bytes".into(), JsonValue::Number(value.bytes as f64));
+
+        JsonValue::Object(map)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{format_bytes, parse_bytes, AllocCounts, HeapSummary};
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn parses_bytes() {
+        assert_eq!(parse_bytes("232 B"), Some(232));
+        assert_eq!(parse_bytes("1.5 KiB"), Some(1536));
+        assert_eq!(parse_bytes("2.0 MiB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_bytes("2.0 PiB"), None);
+    }
+
+    #[test]
+    fn round_trips_summary_lines() {
+        let summary = HeapSummary {
+            total_bytes: 276,
+            peak_bytes: 232,
+            allocations: 3,
+        };
+        let line = format!("Part 1: 42 (4.1ms) {summary}");
+        assert_eq!(
+            line,
+            "Part 1: 42 (4.1ms) [heap: 276 B total, 232 B peak, 3 allocs]"
+        );
+        assert_eq!(HeapSummary::parse_line(&line), Some(summary));
+        assert_eq!(HeapSummary::parse_line("Part 1: 42 (4.1ms)"), None);
+    }
+
+    #[test]
+    fn averages_alloc_counts() {
+        let start = AllocCounts {
+            allocations: 10,
+            bytes: 1024,
+        };
+        let end = AllocCounts {
+            allocations: 40,
+            bytes: 4096,
+        };
+        let counts = AllocCounts::per_iteration(start, end, 3);
+        assert_eq!(
+            counts,
+            AllocCounts {
+                allocations: 10,
+                bytes: 1024
+            }
+        );
+        assert_eq!(counts.to_string(), "[alloc: 10 allocs/iter, 1.0 KiB/iter]");
+    }
+}
@@ -1,14 +1,28 @@
 use std::{env, fs};
 
+use config::Config;
+
 pub mod aoc_cli;
+pub mod args;
+pub mod benchmark_report;
 pub mod commands;
+pub mod config;
+pub mod differential;
+pub mod memory;
+pub mod options;
 pub mod runner;
+pub mod visualize;
 
 pub use day::*;
+pub use day_selection::*;
 
 mod day;
+mod day_selection;
+mod history;
+mod readme;
 mod readme_benchmarks;
 mod run_multi;
+mod stars;
 mod timings;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
@@ -18,8 +32,15 @@ pub const ANSI_RESET: &str = "\x1b[0m";
 /// Helper function that reads a text file to a string.
 #[must_use]
 pub fn read_file(folder: &str, day: Day) -> String {
+    let config = Config::load().expect("could not load config");
+    read_file_with(&config, folder, day)
+}
+
+/// Helper function that reads a text file from the configured data directory to a string.
+#[must_use]
+pub fn read_file_with(config: &Config, folder: &str, day: Day) -> String {
     let cwd = env::current_dir().unwrap();
-    let filepath = cwd.join("data").join(folder).join(format!("{day}.txt"));
+    let filepath = cwd.join(config.data_file(folder, day, "txt"));
     let f = fs::read_to_string(filepath);
     f.expect("could not open input file")
 }
@@ -27,9 +48,10 @@ pub fn read_file(folder: &str, day: Day) -> String {
 /// Helper function that reads a text file to string, appending a part suffix. E.g. like `01-2.txt`.
 #[must_use]
 pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
+    let config = Config::load().expect("could not load config");
     let cwd = env::current_dir().unwrap();
     let filepath = cwd
-        .join("data")
+        .join(&config.data_dir)
         .join(folder)
         .join(format!("{day}-{part}.txt"));
     let f = fs::read_to_string(filepath);
@@ -39,19 +61,46 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+///
+/// Alternative implementations of a part can be registered as named variants. They are compared
+/// with `cargo solve <day> --variants`, the regular run still uses `part_one` and `part_two`:
+///
+/// ```ignore
+/// advent_of_code::solution!(13, variants {
+///     1 => [equation = part_one, walkback = part_one_walkback],
+/// });
+/// ```
+///
+/// An input generator `fn(seed: u64, size: usize) -> String` can be registered to bench how the
+/// solution scales with `cargo stress <day>`. `size` is relative to the real puzzle input:
+///
+/// ```ignore
+/// advent_of_code::solution!(9, generator = generate);
+/// ```
+///
+/// A report `fn(input: &str)` can be registered to print details about the solution, e.g. why a
+/// part has no answer or what was asked for with `--option`. It runs once after the parts,
+/// outside of the timed runs, and decides itself whether there is anything to print:
+///
+/// ```ignore
+/// advent_of_code::solution!(2, report = report);
+/// ```
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2];;;);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, [part_one, 1];;;);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, [part_two, 2];;;);
+    };
+    ($day:expr $(, generator = $generator:path)? $(, report = $report:path)? $(, variants { $( $vpart:literal => [ $( $name:ident = $vfunc:path ),+ $(,)? ] ),+ $(,)? })? $(,)?) => {
+        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]; $( $( $vpart => [ $( $name = $vfunc ),+ ] )+ )?; $( $generator )?; $( $report )?);
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, $( [$func:expr, $part:expr] )*; $( $vpart:literal => [ $( $name:ident = $vfunc:path ),+ ] )*; $( $generator:path )?; $( $report:path )?) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -59,10 +108,48 @@ macro_rules! solution {
         #[global_allocator]
         static ALLOC: dhat::Alloc = dhat::Alloc;
 
+        #[cfg(all(feature = "count-alloc", not(feature = "dhat-heap")))]
+        #[global_allocator]
+        static ALLOC: $crate::template::memory::CountingAlloc = $crate::template::memory::CountingAlloc;
+
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let args = $crate::template::args::SolutionArguments::from_env();
+            let config = $crate::template::config::Config::load_or_exit().with_overrides(&args.overrides);
+            start_watchdog(config.timeout);
+
+            if let Some(max_size) = args.stress {
+                let generator: Option<Generator> = None $( .or(Some($generator)) )?;
+                let Some(generate) = generator else {
+                    eprintln!("Day {DAY} does not register an input generator.");
+                    std::process::exit(1);
+                };
+
+                let seed = args.seed.unwrap_or(1);
+                run_stress(generate, &[ $( ($part, &|input: &str| $func(input).map(|r| r.to_string())) ),* ], DAY, max_size, seed, &config);
+                return;
+            }
+
+            let input = $crate::template::read_file_with(&config, "inputs", DAY);
+
+            if args.variants {
+                let registered: &[u8] = &[$( $vpart ),*];
+                if registered.is_empty() {
+                    eprintln!("Day {DAY} does not register any variants.");
+                    std::process::exit(1);
+                }
+
+                let mut agree = true;
+                $( agree &= run_variants(&[ $( (stringify!($name), &$vfunc as &dyn Fn(&str) -> _) ),+ ], &input, DAY, $vpart, &config); )*
+                if !agree {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            $( run_part($func, &input, DAY, $part, &args, &config); )*
+
+            $( $report(&input); )?
         }
     };
 }
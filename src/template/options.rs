@@ -0,0 +1,93 @@
+/// Puzzle-specific options, passed to a solution with `--option KEY[=VALUE]`. Solutions read them
+/// with [`is_set`] and [`value`], e.g. to print diagnostics or to change a puzzle parameter.
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+static OPTIONS: OnceLock<Vec<SolutionOption>> = OnceLock::new();
+
+/// A single `--option`, e.g. `diagnose` or `blinks=100`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionOption {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl FromStr for SolutionOption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = match s.split_once('=') {
+            Some((key, value)) => (key, Some(value.to_string())),
+            None => (s, None),
+        };
+
+        if key.is_empty() {
+            return Err("expecting `KEY` or `KEY=VALUE`".into());
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            value,
+        })
+    }
+}
+
+impl Display for SolutionOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={value}", self.key),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+/// Stores the options of the running solution. Only the first call has an effect.
+pub(crate) fn init(options: &[SolutionOption]) {
+    let _ = OPTIONS.set(options.to_vec());
+}
+
+fn find(key: &str) -> Option<&'static SolutionOption> {
+    OPTIONS.get()?.iter().rev().find(|option| option.key == key)
+}
+
+/// Whether the option was passed, with or without a value.
+pub fn is_set(key: &str) -> bool {
+    find(key).is_some()
+}
+
+/// The value of the option, or `None` if it was not passed. The last value wins if the option
+/// was passed several times.
+///
+/// Exits the process with an error if the value is missing or cannot be parsed, as with invalid
+/// command-line arguments.
+pub fn value<T: FromStr>(key: &str) -> Option<T> {
+    let option = find(key)?;
+    let parsed = option.value.as_deref().map(str::parse);
+    match parsed {
+        Some(Ok(value)) => Some(value),
+        Some(Err(_)) | None => {
+            eprintln!("Error: invalid value for option `{key}`: `{option}`");
+            std::process::exit(1);
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::SolutionOption;
+
+    #[test]
+    fn parses_options() {
+        let option: SolutionOption = "blinks=100".parse().unwrap();
+        assert_eq!(option.key, "blinks");
+        assert_eq!(option.value.as_deref(), Some("100"));
+        assert_eq!(option.to_string(), "blinks=100");
+
+        let flag: SolutionOption = "diagnose".parse().unwrap();
+        assert_eq!(flag.value, None);
+        assert!("=1".parse::<SolutionOption>().is_err());
+    }
+}
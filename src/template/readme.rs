@@ -0,0 +1,96 @@
+/// Replaces marker-delimited sections of the readme, e.g. the benchmark and stars tables.
+/// The approach taken is similar to how `aoc-readme-stars` handles this.
+use std::fmt::Display;
+use std::io;
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum Error {
+    Parser(String),
+    IO(io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parser(msg) => f.write_str(msg),
+            Error::IO(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IO(e)
+    }
+}
+
+pub struct TablePosition {
+    pos_start: usize,
+    pos_end: usize,
+}
+
+fn locate_table(readme: &str, marker: &str) -> Result<TablePosition, Error> {
+    let matches: Vec<_> = readme.match_indices(marker).collect();
+
+    if matches.len() > 2 {
+        return Err(Error::Parser(format!(
+            "{marker}: too many occurences of marker in README."
+        )));
+    }
+
+    let pos_start = matches
+        .first()
+        .map(|m| m.0)
+        .ok_or_else(|| Error::Parser("Could not find table start position.".into()))?;
+
+    let pos_end = matches
+        .last()
+        .map(|m| m.0 + m.1.len())
+        .ok_or_else(|| Error::Parser("Could not find table end position.".into()))?;
+
+    Ok(TablePosition { pos_start, pos_end })
+}
+
+/// Returns the content between the two markers, without the markers.
+pub fn table_content<'a>(readme: &'a str, marker: &str) -> Result<&'a str, Error> {
+    let positions = locate_table(readme, marker)?;
+    let content = &readme[positions.pos_start..positions.pos_end];
+    Ok(content
+        .strip_prefix(marker)
+        .and_then(|content| content.strip_suffix(marker))
+        .unwrap_or_default())
+}
+
+/// Replaces the section between the two markers with `lines`, keeping the markers.
+pub fn replace_table(readme: &mut String, marker: &str, lines: &[String]) -> Result<(), Error> {
+    let positions = locate_table(readme, marker)?;
+    let mut table: Vec<&str> = vec![marker];
+    table.extend(lines.iter().map(String::as_str));
+    table.push(marker);
+    readme.replace_range(positions.pos_start..positions.pos_end, &table.join("\n"));
+    Ok(())
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{replace_table, table_content};
+
+    static MARKER: &str = "<!--- table --->";
+
+    #[test]
+    fn replaces_tables() {
+        let mut s = format!("foo\n{MARKER}\nold\n{MARKER}\nbar");
+        replace_table(&mut s, MARKER, &["new".into(), "rows".into()]).unwrap();
+        assert_eq!(s, format!("foo\n{MARKER}\nnew\nrows\n{MARKER}\nbar"));
+        assert_eq!(table_content(&s, MARKER).unwrap(), "\nnew\nrows\n");
+    }
+
+    #[test]
+    fn errors_on_missing_or_extra_markers() {
+        assert!(replace_table(&mut "# readme".to_string(), MARKER, &[]).is_err());
+        assert!(table_content(&format!("{MARKER} {MARKER} {MARKER}"), MARKER).is_err());
+    }
+}
@@ -1,5 +1,6 @@
-use std::{collections::HashSet, io};
+use std::{collections::HashSet, io, path::Path};
 
+use crate::template::config::{ConfigOverrides, OutputFormat};
 use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
 
 use super::{
@@ -7,7 +8,19 @@ use super::{
     timings::{Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    is_timed: bool,
+    count_allocations: bool,
+    overrides: &ConfigOverrides,
+) -> Option<Timings> {
+    // NOTE: the output of child commands is parsed, so it always has to be text.
+    let overrides = ConfigOverrides {
+        format: Some(OutputFormat::Text),
+        ..overrides.clone()
+    };
+
     let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
 
     let mut need_space = false;
@@ -24,7 +37,14 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
             println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
             println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+            let output = child_commands::run_solution(
+                day,
+                is_timed,
+                is_release,
+                count_allocations,
+                &overrides,
+            )
+            .unwrap();
 
             if output.is_empty() {
                 println!("Not solved.");
@@ -64,43 +84,71 @@ pub fn get_path_for_bin(day: Day) -> String {
     format!("./src/bin/{day}.rs")
 }
 
+/// Checks whether a solution binary has been scaffolded for `day`.
+#[must_use]
+pub fn bin_exists(day: Day) -> bool {
+    Path::new(&get_path_for_bin(day)).exists()
+}
+
 /// All solutions live in isolated binaries.
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
 pub mod child_commands {
-    use super::{get_path_for_bin, Error};
+    use super::{bin_exists, Error};
+    use crate::template::config::ConfigOverrides;
+    use crate::template::memory::HeapSummary;
     use crate::template::Day;
     use std::{
         io::{BufRead, BufReader},
-        path::Path,
         process::{Command, Stdio},
         thread,
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        count_allocations: bool,
+        overrides: &ConfigOverrides,
+    ) -> Result<Vec<String>, Error> {
         // skip command invocation for days that have not been scaffolded yet.
-        if !Path::new(&get_path_for_bin(day)).exists() {
+        if !bin_exists(day) {
             return Ok(vec![]);
         }
 
-        let day_padded = day.to_string();
-        let mut args = vec!["run", "--quiet", "--bin", &day_padded];
+        let mut args = vec![
+            "run".to_string(),
+            "--quiet".to_string(),
+            "--bin".to_string(),
+            day.to_string(),
+        ];
 
         if is_release {
-            args.push("--release");
+            args.push("--release".to_string());
+        }
+
+        if count_allocations {
+            args.extend(["--features".to_string(), "count-alloc".to_string()]);
         }
 
+        args.push("--".to_string());
+
         if is_timed {
             // mirror `--time` flag to child invocations.
-            args.push("--");
-            args.push("--time");
+            args.push("--time".to_string());
         }
 
-        // spawn child command with piped stdout/stderr.
-        // forward output to stdout/stderr while grabbing stdout lines.
+        args.extend(overrides.to_args());
+
+        run_cargo(&args)
+    }
 
+    /// Run cargo with the given arguments.
+    /// Output is forwarded to stdout/stderr while the stdout lines are collected and returned.
+    pub fn run_cargo(args: &[String]) -> Result<Vec<String>, Error> {
+        // spawn child command with piped stdout/stderr.
         let mut cmd = Command::new("cargo")
-            .args(&args)
+            .args(args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()?;
@@ -134,8 +182,26 @@ pub mod child_commands {
             part_1: None,
             part_2: None,
             total_nanos: 0_f64,
+            part_1_heap: None,
+            part_2_heap: None,
+            median_nanos: None,
+            min_nanos: None,
+            samples: None,
         };
 
+        // heap statistics are printed on the result line when running with `--dhat`.
+        for line in output {
+            let Some(heap) = HeapSummary::parse_line(line) else {
+                continue;
+            };
+
+            if line.starts_with("Part 1") {
+                timings.part_1_heap = Some(heap);
+            } else if line.starts_with("Part 2") {
+                timings.part_2_heap = Some(heap);
+            }
+        }
+
         output
             .iter()
             .filter_map(|l| {
@@ -161,30 +227,51 @@ pub mod child_commands {
                 timings.total_nanos += nanos;
             });
 
+        // bench statistics are summed up over both parts, like `total_nanos`.
+        for (median, min, samples) in output.iter().filter_map(|l| parse_bench_stats(l)) {
+            timings.median_nanos = Some(timings.median_nanos.unwrap_or(0_f64) + median);
+            timings.min_nanos = Some(timings.min_nanos.unwrap_or(0_f64) + min);
+            timings.samples = Some(timings.samples.map_or(samples, |x| x.min(samples)));
+        }
+
         timings
     }
 
+    /// Parses median, min and sample count from a benched line, e.g.
+    /// `Part 1: 42 (4.1ms @ 243 samples) [median: 4.0ms, min: 3.9ms]`.
+    fn parse_bench_stats(line: &str) -> Option<(f64, f64, u64)> {
+        let (measurement, stats) = line.split_once(" samples) [median: ")?;
+        let samples = measurement.rsplit_once('@')?.1.trim().parse().ok()?;
+        let (median, min) = stats.split_once(']')?.0.split_once(", min: ")?;
+        Some((parse_duration(median)?, parse_duration(min)?, samples))
+    }
+
     fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
         s.split(postfix).next()?.parse().ok()
     }
 
-    fn parse_time(line: &str) -> Option<(&str, f64)> {
+    /// Parses a duration formatted with `{:.1?}` to nanoseconds.
+    fn parse_duration(s: &str) -> Option<f64> {
         // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
+        match s.trim() {
+            s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
+            s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
+            s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
+            s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
+        }
+    }
+
+    fn parse_time(line: &str) -> Option<(&str, f64)> {
         let str_timing = line
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
 
-        let parsed_timing = match str_timing {
-            s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
-            s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
-            s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
-            s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
-        }?;
+        let parsed_timing = parse_duration(str_timing)?;
 
         Some((str_timing, parsed_timing))
     }
@@ -253,5 +340,38 @@ pub mod child_commands {
             assert_eq!(res.part_1.is_none(), true);
             assert_eq!(res.part_2.is_none(), true);
         }
+
+        #[test]
+        fn parses_bench_stats() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 0 (80.0ns @ 1000 samples) [median: 74.1ns, min: 70.0ns]".into(),
+                    "Part 2: 10 (2.0ms @ 500 samples) [median: 1.5ms, min: 1.0ms]".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_approx_eq!(res.median_nanos.unwrap(), 1500074.1_f64);
+            assert_approx_eq!(res.min_nanos.unwrap(), 1000070_f64);
+            assert_eq!(res.samples, Some(500));
+            assert_eq!(res.part_1.unwrap(), "80.0ns");
+        }
+
+        #[test]
+        fn parses_heap_summaries() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 0 (74.13ns) [heap: 1.5 KiB total, 232 B peak, 3 allocs]".into(),
+                    "Part 2: 10 (74.13ms)".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            let heap = res.part_1_heap.unwrap();
+            assert_eq!(heap.total_bytes, 1536);
+            assert_eq!(heap.peak_bytes, 232);
+            assert_eq!(heap.allocations, 3);
+            assert_eq!(res.part_2_heap.is_none(), true);
+        }
     }
 }
@@ -1,65 +1,499 @@
 /// Encapsulates code that interacts with solution functions.
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::hint::black_box;
 use std::io::{stdout, Write};
 use std::process::Output;
 use std::time::{Duration, Instant};
-use std::{cmp, env, process};
+use std::{cmp, process, thread};
 
+use tinyjson::JsonValue;
+
+use crate::memo;
+use crate::template::args::{Part, Scale, SolutionArguments};
+use crate::template::benchmark_report::render_bar;
+use crate::template::config::{BenchConfig, Config, OutputFormat};
+use crate::template::memory::{current_heap_summary, format_bytes, AllocCounts, HeapSummary};
 use crate::template::ANSI_BOLD;
-use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{aoc_cli, stars, visualize, Day, ANSI_ITALIC, ANSI_RESET};
 
-pub fn run_part<I: Copy, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+pub fn run_part<I: Copy, T: Display>(
+    func: impl Fn(I) -> Option<T>,
+    input: I,
+    day: Day,
+    part: u8,
+    args: &SolutionArguments,
+    config: &Config,
+) {
     let part_str = format!("Part {part}");
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let (result, measurement) = match config.format {
+        OutputFormat::Text => run_timed(
+            &func,
+            input,
+            |result| {
+                print_result(result, &part_str, "");
+                if args.time {
+                    print_benching();
+                }
+            },
+            args.time.then_some(&config.bench),
+        ),
+        OutputFormat::Json => run_timed(&func, input, |_| {}, args.time.then_some(&config.bench)),
+    };
+
+    match config.format {
+        OutputFormat::Text => print_result(&result, &part_str, &format_measurement(&measurement)),
+        OutputFormat::Json => print_json_result(&result, day, part, &measurement),
+    }
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    // frames are recorded on a separate, untimed run, so recording never affects the measurement.
+    if let Some(target) = args.visualize {
+        visualize::start();
+        func(input);
+        visualize::present(&visualize::stop(), target, day, part, config);
+    }
 
     if let Some(result) = result {
-        submit_result(result, day, part);
+        let answer = result.to_string();
+        if let Some(Ok(output)) = submit_result(result, day, part, args.submit, config) {
+            if aoc_cli::is_correct_answer(&output) {
+                match stars::record_star(day, part, &answer, config) {
+                    Ok(()) => println!("⭐ Recorded the star for day {day} part {part}."),
+                    Err(e) => eprintln!("Failed to record the star: {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// A named implementation of a part, registered with the `variants` block of [`solution!`](crate::solution).
+pub type Variant<'a, I, T> = (&'a str, &'a dyn Fn(I) -> Option<T>);
+
+/// Runs every variant of a part, checks that they agree on the result and benches them against each other.
+/// Returns `false` if the variants disagree.
+pub fn run_variants<I: Copy, T: Display>(
+    variants: &[Variant<I, T>],
+    input: I,
+    day: Day,
+    part: u8,
+    config: &Config,
+) -> bool {
+    let mut rows = vec![];
+
+    for (name, func) in variants {
+        if config.format == OutputFormat::Text {
+            print!("\r{ANSI_BOLD}Part {part}{ANSI_RESET}: {name}");
+            let _ = stdout().flush();
+        }
+
+        let (result, measurement) = run_timed(
+            func,
+            input,
+            |_| {
+                if config.format == OutputFormat::Text {
+                    print_benching();
+                }
+            },
+            Some(&config.bench),
+        );
+        let result = result.map(|r| r.to_string());
+
+        if config.format == OutputFormat::Json {
+            print_json_variant(&result, day, part, name, &measurement);
+        }
+
+        rows.push((*name, result, measurement));
+    }
+
+    let agree = rows.windows(2).all(|pair| pair[0].1 == pair[1].1);
+
+    if config.format == OutputFormat::Text {
+        // clear the progress line.
+        print!("\r{}\r", " ".repeat(80));
+        println!("{}", format_variants(part, &rows, agree));
+    }
+
+    agree
+}
+
+fn format_variants(part: u8, rows: &[(&str, Option<String>, Measurement)], agree: bool) -> String {
+    let fastest = rows
+        .iter()
+        .map(|(_, _, m)| m.duration)
+        .min()
+        .unwrap_or_default();
+
+    let mut table = vec![[
+        "Variant".to_string(),
+        "Result".into(),
+        "Time".into(),
+        "Samples".into(),
+        "Relative".into(),
+    ]];
+
+    for (name, result, measurement) in rows {
+        let relative = measurement.duration.as_secs_f64() / fastest.as_secs_f64().max(f64::EPSILON);
+        table.push([
+            (*name).to_string(),
+            result.clone().unwrap_or_else(|| "✖".into()),
+            format!("{:.1?}", measurement.duration),
+            measurement.samples.to_string(),
+            format!("{relative:.2}x"),
+        ]);
+    }
+
+    let widths = (0..table[0].len())
+        .map(|col| {
+            table
+                .iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let status = if agree {
+        format!("all {} variants agree", rows.len())
+    } else {
+        "variants disagree ✖".to_string()
+    };
+
+    let mut lines = vec![format!("{ANSI_BOLD}Part {part}{ANSI_RESET}: {status}")];
+    for row in &table {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>();
+        lines.push(format!("  {}", cells.join("  ").trim_end()));
+    }
+
+    lines.join("\n")
+}
+
+fn print_json_variant(
+    result: &Option<String>,
+    day: Day,
+    part: u8,
+    variant: &str,
+    measurement: &Measurement,
+) {
+    let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+    map.insert("day".into(), JsonValue::String(day.to_string()));
+    map.insert("part".into(), JsonValue::Number(f64::from(part)));
+    map.insert("variant".into(), JsonValue::String(variant.into()));
+    map.insert(
+        "result".into(),
+        result.clone().map_or(JsonValue::Null, JsonValue::String),
+    );
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "nanos".into(),
+        JsonValue::Number(measurement.duration.as_nanos() as f64),
+    );
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "samples".into(),
+        JsonValue::Number(measurement.samples as f64),
+    );
+
+    match JsonValue::Object(map).stringify() {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Could not serialize result: {e}"),
+    }
+}
+
+/// Generates a puzzle input from a seed, roughly `size` times as large as the real puzzle input.
+pub type Generator = fn(u64, usize) -> String;
+
+/// A part benched by [`run_stress`], with its result converted to a string.
+pub type StressPart<'a> = (u8, &'a dyn Fn(&str) -> Option<String>);
+
+/// Time growth between two sizes above which a part is flagged as superlinear, e.g. `n^1.5`.
+const SUPERLINEAR_EXPONENT: f64 = 1.5;
+
+/// The timings of all parts on a generated input.
+struct StressRow {
+    size: u32,
+    input_bytes: usize,
+    times: Vec<Duration>,
+}
+
+/// Benches every part on generated inputs of doubling size, up to `max_size`, and prints how the
+/// time grows with the input size.
+pub fn run_stress(
+    generate: Generator,
+    parts: &[StressPart],
+    day: Day,
+    max_size: Scale,
+    seed: u64,
+    config: &Config,
+) {
+    let mut rows = vec![];
+
+    for size in stress_sizes(max_size.into_inner()) {
+        let input = generate(seed, size as usize);
+
+        let times = parts
+            .iter()
+            .map(|(part, func)| {
+                if config.format == OutputFormat::Text {
+                    print!("\r{ANSI_BOLD}{size}x{ANSI_RESET}: part {part}");
+                    let _ = stdout().flush();
+                }
+
+                let (_, measurement) = run_timed(
+                    func,
+                    input.as_str(),
+                    |_| {
+                        if config.format == OutputFormat::Text {
+                            print_benching();
+                        }
+                    },
+                    Some(&config.bench),
+                );
+
+                if config.format == OutputFormat::Json {
+                    print_json_stress(day, *part, size, input.len(), &measurement);
+                }
+
+                measurement.median
+            })
+            .collect();
+
+        rows.push(StressRow {
+            size,
+            input_bytes: input.len(),
+            times,
+        });
+    }
+
+    if config.format == OutputFormat::Text {
+        // clear the progress line.
+        print!("\r{}\r", " ".repeat(80));
+        let parts = parts.iter().map(|(part, _)| *part).collect::<Vec<_>>();
+        println!("{}", format_stress(day, seed, &parts, &rows));
+    }
+}
+
+/// Doubles the size until `max_size` is reached, e.g. `1, 2, 4, 8, 10` for `10`.
+fn stress_sizes(max_size: u32) -> Vec<u32> {
+    let mut sizes: Vec<u32> = std::iter::successors(Some(1_u32), |size| size.checked_mul(2))
+        .take_while(|size| *size < max_size)
+        .collect();
+    sizes.push(max_size);
+    sizes
+}
+
+/// Estimates `k` in `time ~ n^k` between two measurements of input size `n`.
+#[allow(clippy::cast_precision_loss)]
+fn growth_exponent(from: (usize, Duration), to: (usize, Duration)) -> Option<f64> {
+    let bytes = to.0 as f64 / from.0 as f64;
+    let time = to.1.as_secs_f64() / from.1.as_secs_f64();
+    (bytes > 1.0 && time.is_finite() && time > 0.0).then(|| time.ln() / bytes.ln())
+}
+
+fn format_stress(day: Day, seed: u64, parts: &[u8], rows: &[StressRow]) -> String {
+    let slowest = rows
+        .iter()
+        .map(|row| row.times.iter().sum::<Duration>())
+        .max()
+        .unwrap_or_default();
+
+    let mut header = vec!["Size".to_string(), "Input".into()];
+    for part in parts {
+        header.extend([format!("Part {part}"), "Growth".into()]);
+    }
+    header.push("Curve".into());
+
+    let mut table = vec![header];
+    let mut superlinear = false;
+
+    for (idx, row) in rows.iter().enumerate() {
+        let mut cells = vec![
+            format!("{}x", row.size),
+            format_bytes(row.input_bytes as u64),
+        ];
+
+        for (col, time) in row.times.iter().enumerate() {
+            let growth = idx
+                .checked_sub(1)
+                .and_then(|prev| {
+                    let prev = &rows[prev];
+                    growth_exponent(
+                        (prev.input_bytes, prev.times[col]),
+                        (row.input_bytes, *time),
+                    )
+                })
+                .map_or_else(
+                    || "-".to_string(),
+                    |k| {
+                        if k >= SUPERLINEAR_EXPONENT {
+                            superlinear = true;
+                            format!("n^{k:.2} ⚠")
+                        } else {
+                            format!("n^{k:.2}")
+                        }
+                    },
+                );
+            cells.extend([format!("{time:.1?}"), growth]);
+        }
+
+        let total = row.times.iter().sum::<Duration>();
+        cells.push(render_bar(
+            total.as_secs_f64() / slowest.as_secs_f64().max(f64::EPSILON),
+        ));
+        table.push(cells);
     }
+
+    let widths = (0..table[0].len())
+        .map(|col| {
+            table
+                .iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![format!(
+        "{ANSI_BOLD}Day {day}{ANSI_RESET}: stress test (seed {seed})"
+    )];
+    for row in &table {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>();
+        lines.push(format!("  {}", cells.join("  ").trim_end()));
+    }
+
+    if superlinear {
+        lines.push(format!(
+            "\n⚠ time grows faster than n^{SUPERLINEAR_EXPONENT} with the input size n."
+        ));
+    }
+
+    lines.join("\n")
 }
 
-/// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
-///  1. in debug, the function is executed once.
-///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+fn print_json_stress(day: Day, part: u8, size: u32, input_bytes: usize, measurement: &Measurement) {
+    let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+    map.insert("day".into(), JsonValue::String(day.to_string()));
+    map.insert("part".into(), JsonValue::Number(f64::from(part)));
+    map.insert("size".into(), JsonValue::Number(f64::from(size)));
+    #[allow(clippy::cast_precision_loss)]
+    map.insert("input_bytes".into(), JsonValue::Number(input_bytes as f64));
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "median_nanos".into(),
+        JsonValue::Number(measurement.median.as_nanos() as f64),
+    );
+
+    match JsonValue::Object(map).stringify() {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Could not serialize result: {e}"),
+    }
+}
+
+/// Aborts the process if the solution is still running after `timeout`.
+pub fn start_watchdog(timeout: Option<Duration>) {
+    if let Some(timeout) = timeout {
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            eprintln!("\nSolution timed out after {timeout:.1?}.");
+            process::exit(124);
+        });
+    }
+}
+
+/// Timing and heap statistics of a solution part.
+struct Measurement {
+    /// Average time of all samples.
+    duration: Duration,
+    median: Duration,
+    min: Duration,
+    samples: u128,
+    /// Heap usage of the first run, only collected with the `dhat-heap` feature.
+    heap: Option<HeapSummary>,
+    /// Allocations per bench iteration, only collected with the `count-alloc` feature.
+    allocs: Option<AllocCounts>,
+}
+
+/// Run a solution part. The behavior differs depending on whether the part is benched:
+///  1. without bench settings, the function is executed once.
+///  2. with bench settings, the function is benched (approx. `budget` of execution time, clamped to the configured number of samples.)
+///     With the `count-alloc` feature, the allocations per iteration are counted as well.
 fn run_timed<I: Copy, T>(
     func: impl Fn(I) -> T,
     input: I,
     hook: impl Fn(&T),
-) -> (T, Duration, u128) {
+    bench_config: Option<&BenchConfig>,
+) -> (T, Measurement) {
+    // every run starts with empty part-scoped memo tables, including every bench sample.
+    let func = |input| {
+        memo::start_part();
+        func(input)
+    };
+
     let timer = Instant::now();
-    let result = {
+    let (result, heap) = {
         #[cfg(feature = "dhat-heap")]
         let _profiler = dhat::Profiler::new_heap();
 
-        func(input)
+        let result = func(input);
+        // NOTE: the stats have to be read before the profiler is dropped.
+        (result, current_heap_summary())
     };
     let base_time = timer.elapsed();
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    let measurement = if let Some(bench_config) = bench_config {
+        bench(func, input, &base_time, bench_config)
     } else {
-        (base_time, 1)
+        Measurement {
+            duration: base_time,
+            median: base_time,
+            min: base_time,
+            samples: 1,
+            heap: None,
+            allocs: None,
+        }
     };
 
-    (result, run.0, run.1)
+    (
+        result,
+        Measurement {
+            heap,
+            ..measurement
+        },
+    )
 }
 
-fn bench<I: Copy, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
-    let mut stdout = stdout();
-
+/// Appends the progress note of a part that is being benched to its intermediate text output.
+fn print_benching() {
     print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
-    let _ = stdout.flush();
+    let _ = stdout().flush();
+}
+
+fn bench<I: Copy, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+    config: &BenchConfig,
+) -> Measurement {
+    let bench_iterations = (config.budget.as_nanos() / cmp::max(base_time.as_nanos(), 10))
+        .clamp(config.min_samples, config.max_samples);
 
-    let bench_iterations =
-        (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
+    // NOTE: reserve upfront so that the timers do not show up in the allocation counts.
+    #[allow(clippy::cast_possible_truncation)]
+    let mut timers: Vec<Duration> = Vec::with_capacity(bench_iterations as usize);
 
-    let mut timers: Vec<Duration> = vec![];
+    let allocs_start = AllocCounts::current();
 
     for _ in 0..bench_iterations {
         let timer = Instant::now();
@@ -67,11 +501,23 @@ fn bench<I: Copy, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (
         timers.push(timer.elapsed());
     }
 
-    (
-        #[allow(clippy::cast_possible_truncation)]
-        Duration::from_nanos(average_duration(&timers) as u64),
-        bench_iterations,
-    )
+    let allocs = allocs_start
+        .zip(AllocCounts::current())
+        .map(|(start, end)| AllocCounts::per_iteration(start, end, bench_iterations));
+
+    #[allow(clippy::cast_possible_truncation)]
+    let duration = Duration::from_nanos(average_duration(&timers) as u64);
+
+    timers.sort_unstable();
+
+    Measurement {
+        duration,
+        median: timers[timers.len() / 2],
+        min: timers[0],
+        samples: bench_iterations,
+        heap: None,
+        allocs,
+    }
 }
 
 fn average_duration(numbers: &[Duration]) -> u128 {
@@ -82,12 +528,31 @@ fn average_duration(numbers: &[Duration]) -> u128 {
         / numbers.len() as u128
 }
 
-fn format_duration(duration: &Duration, samples: u128) -> String {
-    if samples == 1 {
+fn format_measurement(measurement: &Measurement) -> String {
+    let Measurement {
+        duration,
+        median,
+        min,
+        samples,
+        heap,
+        allocs,
+    } = measurement;
+
+    let mut s = if *samples == 1 {
         format!(" ({duration:.1?})")
     } else {
-        format!(" ({duration:.1?} @ {samples} samples)")
+        format!(" ({duration:.1?} @ {samples} samples) [median: {median:.1?}, min: {min:.1?}]")
+    };
+
+    if let Some(heap) = heap {
+        s.push_str(&format!(" {heap}"));
     }
+
+    if let Some(allocs) = allocs {
+        s.push_str(&format!(" {allocs}"));
+    }
+
+    s
 }
 
 fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
@@ -125,41 +590,102 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
     }
 }
 
-/// Parse the arguments passed to `solve` and try to submit one part of the solution if:
-///  1. we are in `--release` mode.
+fn print_json_result<T: Display>(
+    result: &Option<T>,
+    day: Day,
+    part: u8,
+    measurement: &Measurement,
+) {
+    let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+    map.insert("day".into(), JsonValue::String(day.to_string()));
+    map.insert("part".into(), JsonValue::Number(f64::from(part)));
+    map.insert(
+        "result".into(),
+        match result {
+            Some(result) => JsonValue::String(result.to_string()),
+            None => JsonValue::Null,
+        },
+    );
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "nanos".into(),
+        JsonValue::Number(measurement.duration.as_nanos() as f64),
+    );
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "median_nanos".into(),
+        JsonValue::Number(measurement.median.as_nanos() as f64),
+    );
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "min_nanos".into(),
+        JsonValue::Number(measurement.min.as_nanos() as f64),
+    );
+    #[allow(clippy::cast_precision_loss)]
+    map.insert(
+        "samples".into(),
+        JsonValue::Number(measurement.samples as f64),
+    );
+    if let Some(heap) = &measurement.heap {
+        map.insert("heap".into(), JsonValue::from(heap));
+    }
+    if let Some(allocs) = &measurement.allocs {
+        map.insert("allocs_per_iteration".into(), JsonValue::from(allocs));
+    }
+
+    match JsonValue::Object(map).stringify() {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Could not serialize result: {e}"),
+    }
+}
+
+/// Try to submit one part of the solution if:
+///  1. `--submit` was passed for this part.
 ///  2. aoc-cli is installed.
 fn submit_result<T: Display>(
     result: T,
     day: Day,
     part: u8,
+    submit: Option<Part>,
+    config: &Config,
 ) -> Option<Result<Output, aoc_cli::AocCommandError>> {
-    let args: Vec<String> = env::args().collect();
-
-    if !args.contains(&"--submit".into()) {
+    if submit?.into_inner() != part {
         return None;
     }
 
-    if args.len() < 3 {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
+    if aoc_cli::check().is_err() {
+        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
         process::exit(1);
     }
 
-    let part_index = args.iter().position(|x| x == "--submit").unwrap() + 1;
+    println!("Submitting result via aoc-cli...");
+    Some(aoc_cli::submit(day, part, &result.to_string(), config))
+}
 
-    let Ok(part_submit) = args[part_index].parse::<u8>() else {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
-        process::exit(1);
-    };
+/* -------------------------------------------------------------------------- */
 
-    if part_submit != part {
-        return None;
-    }
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::time::Duration;
 
-    if aoc_cli::check().is_err() {
-        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
+    use super::{growth_exponent, stress_sizes};
+
+    #[test]
+    fn doubles_stress_sizes() {
+        assert_eq!(stress_sizes(1), vec![1]);
+        assert_eq!(stress_sizes(8), vec![1, 2, 4, 8]);
+        assert_eq!(stress_sizes(10), vec![1, 2, 4, 8, 10]);
     }
 
-    println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    #[test]
+    fn estimates_growth() {
+        let ms = Duration::from_millis;
+        let linear = growth_exponent((100, ms(10)), (200, ms(20))).unwrap();
+        assert!((linear - 1.0).abs() < 1e-9);
+        let quadratic = growth_exponent((100, ms(10)), (400, ms(160))).unwrap();
+        assert!((quadratic - 2.0).abs() < 1e-9);
+        assert_eq!(growth_exponent((100, ms(10)), (100, ms(10))), None);
+        assert_eq!(growth_exponent((100, ms(0)), (200, ms(10))), None);
+    }
 }
@@ -0,0 +1,366 @@
+/// Earned stars, recorded in `stars.json` when aoc-cli accepts a submitted answer. The stars
+/// table in the readme is generated from this file.
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+use tinyjson::JsonValue;
+
+use crate::template::config::Config;
+use crate::template::readme::{self, Error};
+use crate::template::{all_days, Day};
+
+static STARS_FILE_NAME: &str = "stars.json";
+static MARKER: &str = "<!--- advent_readme_stars table --->";
+
+/// A solved part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Star {
+    pub day: Day,
+    pub part: u8,
+    /// The accepted answer. Stars imported from an existing readme table do not have one.
+    pub answer: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stars {
+    pub data: Vec<Star>,
+}
+
+impl Stars {
+    /// Dehydrate stars to a JSON file in the configured data directory.
+    pub fn store_file(&self, config: &Config) -> Result<(), std::io::Error> {
+        let json = JsonValue::from(self.clone());
+        let mut file = fs::File::create(config.data_path(STARS_FILE_NAME))?;
+        json.format_to(&mut file)
+    }
+
+    /// Rehydrate stars from a JSON file. Returns `None` if no stars were recorded yet.
+    pub fn read_from_file(config: &Config) -> Option<Self> {
+        fs::read_to_string(config.data_path(STARS_FILE_NAME))
+            .ok()
+            .and_then(|s| Stars::try_from(s).ok())
+    }
+
+    /// Reads the stars of an existing readme table, e.g. one written by the `advent-readme-stars`
+    /// github action before the runner took over.
+    pub fn from_table(table: &str) -> Self {
+        let mut stars = Stars::default();
+
+        for line in table.lines() {
+            let mut cells = line.split('|').map(str::trim).filter(|c| !c.is_empty());
+            let Some(day) = cells
+                .next()
+                .and_then(|cell| cell.strip_prefix("[Day "))
+                .and_then(|cell| cell.split_once(']'))
+                .and_then(|(day, _)| day.parse::<Day>().ok())
+            else {
+                continue;
+            };
+
+            for (part, cell) in (1..=2).zip(cells) {
+                if cell.contains('⭐') {
+                    stars.record(day, part, None);
+                }
+            }
+        }
+
+        stars
+    }
+
+    /// Records a star, keeping the stored answer if the part was already solved.
+    /// Returns whether the star is new.
+    pub fn record(&mut self, day: Day, part: u8, answer: Option<String>) -> bool {
+        if let Some(star) = self
+            .data
+            .iter_mut()
+            .find(|s| s.day == day && s.part == part)
+        {
+            if star.answer.is_none() {
+                star.answer = answer;
+            }
+            return false;
+        }
+
+        self.data.push(Star { day, part, answer });
+        self.data.sort_unstable_by_key(|s| (s.day, s.part));
+        true
+    }
+
+    pub fn find(&self, day: Day, part: u8) -> Option<&Star> {
+        self.data.iter().find(|s| s.day == day && s.part == part)
+    }
+
+    /// Renders the readme table, with a row for every day up to the last solved one.
+    pub fn markdown_table(&self, year: Option<u16>) -> Vec<String> {
+        let mut lines = vec![
+            match year {
+                Some(year) => format!("## {year} Results"),
+                None => "## Results".into(),
+            },
+            String::new(),
+            "| Day | Part 1 | Part 2 |".into(),
+            "| :---: | :---: | :---: |".into(),
+        ];
+
+        let Some(last_day) = self.data.iter().map(|s| s.day).max() else {
+            return lines;
+        };
+
+        for day in all_days().take_while(|day| *day <= last_day) {
+            let link = match year {
+                Some(year) => format!("https://adventofcode.com/{year}/day/{}", day.into_inner()),
+                None => format!("./src/bin/{day}.rs"),
+            };
+            let cell = |part| {
+                if self.find(day, part).is_some() {
+                    "⭐"
+                } else {
+                    " "
+                }
+            };
+            lines.push(format!(
+                "| [Day {}]({link}) | {} | {} |",
+                day.into_inner(),
+                cell(1),
+                cell(2)
+            ));
+        }
+
+        lines
+    }
+}
+
+/// Records a star for an accepted answer in `stars.json` and, if enabled, updates the stars table
+/// in the readme.
+///
+/// The first time a star is recorded, the stars of the existing readme table are imported so
+/// that days solved before are kept. A missing readme is not an error, there is no table to update.
+pub fn record_star(day: Day, part: u8, answer: &str, config: &Config) -> Result<(), Error> {
+    let readme = if config.readme.stars {
+        read_readme(config)?
+    } else {
+        None
+    };
+
+    let mut stars = Stars::read_from_file(config).unwrap_or_else(|| {
+        readme
+            .as_deref()
+            .and_then(|content| readme::table_content(content, MARKER).ok())
+            .map(Stars::from_table)
+            .unwrap_or_default()
+    });
+
+    stars.record(day, part, Some(answer.into()));
+    stars.store_file(config)?;
+
+    if let Some(mut content) = readme {
+        readme::replace_table(&mut content, MARKER, &stars.markdown_table(config.year))?;
+        fs::write(&config.readme.path, &content)?;
+    }
+
+    Ok(())
+}
+
+fn read_readme(config: &Config) -> Result<Option<String>, Error> {
+    match fs::read(&config.readme.path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<Stars> for JsonValue {
+    fn from(value: Stars) -> Self {
+        let data = value
+            .data
+            .iter()
+            .map(|star| {
+                let mut map: HashMap<String, JsonValue> = HashMap::new();
+                map.insert("day".into(), JsonValue::String(star.day.to_string()));
+                map.insert("part".into(), JsonValue::Number(star.part.into()));
+                map.insert(
+                    "answer".into(),
+                    match &star.answer {
+                        Some(answer) => JsonValue::String(answer.clone()),
+                        None => JsonValue::Null,
+                    },
+                );
+                JsonValue::Object(map)
+            })
+            .collect();
+
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert("data".into(), JsonValue::Array(data));
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<String> for Stars {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+
+        let json_data = json
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected JSON document to be an object.")?
+            .get("data")
+            .ok_or("expected JSON document to have key `data`.")?
+            .get::<Vec<JsonValue>>()
+            .ok_or("expected `json.data` to be an array.")?;
+
+        let data = json_data
+            .iter()
+            .map(|value| {
+                let json = value
+                    .get::<HashMap<String, JsonValue>>()
+                    .ok_or("Expected star to be a JSON object.")?;
+
+                let day = json
+                    .get("day")
+                    .and_then(|v| v.get::<String>())
+                    .and_then(|day| Day::from_str(day).ok())
+                    .ok_or("Expected star.day to be a Day struct.")?;
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let part = json
+                    .get("part")
+                    .and_then(|v| v.get::<f64>())
+                    .map(|part| *part as u8)
+                    .filter(|part| (1..=2).contains(part))
+                    .ok_or("Expected star.part to be 1 or 2.")?;
+
+                let answer = json
+                    .get("answer")
+                    .map(|v| if v.is_null() { None } else { v.get::<String>() })
+                    .ok_or("Expected star.answer to be null or string.")?;
+
+                Ok(Star {
+                    day,
+                    part,
+                    answer: answer.cloned(),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Stars { data })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::fs;
+
+    use super::{record_star, Stars, MARKER};
+    use crate::day;
+    use crate::template::config::Config;
+
+    fn get_mock_stars() -> Stars {
+        let mut stars = Stars::default();
+        stars.record(day!(1), 1, Some("142".into()));
+        stars.record(day!(1), 2, Some("281".into()));
+        stars.record(day!(3), 1, None);
+        stars
+    }
+
+    #[test]
+    fn renders_table() {
+        let expected = [
+            "## 2024 Results",
+            "",
+            "| Day | Part 1 | Part 2 |",
+            "| :---: | :---: | :---: |",
+            "| [Day 1](https://adventofcode.com/2024/day/1) | ⭐ | ⭐ |",
+            "| [Day 2](https://adventofcode.com/2024/day/2) |   |   |",
+            "| [Day 3](https://adventofcode.com/2024/day/3) | ⭐ |   |",
+        ];
+        assert_eq!(get_mock_stars().markdown_table(Some(2024)), expected);
+    }
+
+    #[test]
+    fn imports_table() {
+        let table = get_mock_stars().markdown_table(Some(2024)).join("\n");
+        let stars = Stars::from_table(&table);
+        assert_eq!(stars.data.len(), 3);
+        assert!(stars.find(day!(3), 1).is_some());
+        assert!(stars.find(day!(3), 2).is_none());
+        assert!(stars.data.iter().all(|star| star.answer.is_none()));
+    }
+
+    #[test]
+    fn records_answers() {
+        let mut stars = get_mock_stars();
+        assert!(!stars.record(day!(1), 1, Some("0".into())));
+        assert!(!stars.record(day!(3), 1, Some("17".into())));
+        assert!(stars.record(day!(3), 2, Some("18".into())));
+        assert_eq!(
+            stars.find(day!(1), 1).unwrap().answer.as_deref(),
+            Some("142")
+        );
+        assert_eq!(
+            stars.find(day!(3), 1).unwrap().answer.as_deref(),
+            Some("17")
+        );
+    }
+
+    fn temp_config(name: &str, stars: bool) -> Config {
+        let data_dir =
+            std::env::temp_dir().join(format!("aoc-stars-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let mut config = Config {
+            data_dir: data_dir.clone(),
+            ..Config::default()
+        };
+        config.readme.stars = stars;
+        config.readme.path = data_dir.join("README.md");
+        config
+    }
+
+    #[test]
+    fn records_without_readme() {
+        for stars in [false, true] {
+            let config = temp_config(&format!("missing-{stars}"), stars);
+            record_star(day!(2), 1, "4", &config).unwrap();
+            let recorded = Stars::read_from_file(&config).unwrap();
+            assert_eq!(
+                recorded.find(day!(2), 1).unwrap().answer.as_deref(),
+                Some("4")
+            );
+            assert!(!config.readme.path.exists());
+            fs::remove_dir_all(&config.data_dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn updates_readme_table() {
+        let config = temp_config("readme", true);
+        let readme = format!(
+            "# AoC\n\n{MARKER}\n{}\n{MARKER}\n",
+            get_mock_stars().markdown_table(None).join("\n")
+        );
+        fs::write(&config.readme.path, readme).unwrap();
+
+        record_star(day!(2), 2, "9", &config).unwrap();
+        let content = fs::read_to_string(&config.readme.path).unwrap();
+        assert!(content.contains("| [Day 2](./src/bin/02.rs) |   | ⭐ |"));
+        // the stars of the existing table were imported.
+        assert!(content.contains("| [Day 3](./src/bin/03.rs) | ⭐ |   |"));
+        fs::remove_dir_all(&config.data_dir).unwrap();
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let stars = get_mock_stars();
+        let json = tinyjson::JsonValue::from(stars.clone())
+            .stringify()
+            .unwrap();
+        assert_eq!(Stars::try_from(json), Ok(stars));
+    }
+}
@@ -1,9 +1,11 @@
 use std::{collections::HashMap, fs, io::Error, str::FromStr};
 use tinyjson::JsonValue;
 
+use crate::template::config::Config;
+use crate::template::memory::HeapSummary;
 use crate::template::Day;
 
-static TIMINGS_FILE_PATH: &str = "./data/timings.json";
+static TIMINGS_FILE_NAME: &str = "timings.json";
 
 /// Represents benchmark times for a single day.
 #[derive(Clone, Debug)]
@@ -12,6 +14,16 @@ pub struct Timing {
     pub part_1: Option<String>,
     pub part_2: Option<String>,
     pub total_nanos: f64,
+    /// Heap statistics of part 1, recorded by `solve --dhat --store`.
+    pub part_1_heap: Option<HeapSummary>,
+    /// Heap statistics of part 2, recorded by `solve --dhat --store`.
+    pub part_2_heap: Option<HeapSummary>,
+    /// Sum of the median sample times of both parts.
+    pub median_nanos: Option<f64>,
+    /// Sum of the fastest sample times of both parts.
+    pub min_nanos: Option<f64>,
+    /// Lowest number of bench samples of both parts.
+    pub samples: Option<u64>,
 }
 
 /// Represents benchmark times for a set of days.
@@ -22,16 +34,16 @@ pub struct Timings {
 }
 
 impl Timings {
-    /// Dehydrate timings to a JSON file.
-    pub fn store_file(&self) -> Result<(), Error> {
+    /// Dehydrate timings to a JSON file in the configured data directory.
+    pub fn store_file(&self, config: &Config) -> Result<(), Error> {
         let json = JsonValue::from(self.clone());
-        let mut file = fs::File::create(TIMINGS_FILE_PATH)?;
+        let mut file = fs::File::create(config.data_path(TIMINGS_FILE_NAME))?;
         json.format_to(&mut file)
     }
 
     /// Rehydrate timings from a JSON file. If not present, returns empty timings.
-    pub fn read_from_file() -> Self {
-        fs::read_to_string(TIMINGS_FILE_PATH)
+    pub fn read_from_file(config: &Config) -> Self {
+        fs::read_to_string(config.data_path(TIMINGS_FILE_NAME))
             .map_err(|x| x.to_string())
             .and_then(Timings::try_from)
             .unwrap_or_default()
@@ -42,7 +54,15 @@ impl Timings {
         let mut data: Vec<Timing> = vec![];
 
         for timing in &new.data {
-            data.push(timing.clone());
+            let mut timing = timing.clone();
+
+            // keep stored heap statistics when re-benching a day without `--dhat`.
+            if let Some(stored) = self.data.iter().find(|t| t.day == timing.day) {
+                timing.part_1_heap = timing.part_1_heap.or(stored.part_1_heap);
+                timing.part_2_heap = timing.part_2_heap.or(stored.part_2_heap);
+            }
+
+            data.push(timing);
         }
 
         for timing in &self.data {
@@ -51,15 +71,45 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
+        data.sort_unstable_by_key(|a| a.day);
         Timings { data }
     }
 
+    /// Merge the heap statistics of `new` into `self`, keeping the stored run times.
+    pub fn merge_heap(&self, new: &Self) -> Self {
+        let mut merged = self.clone();
+
+        for timing in &new.data {
+            match merged.data.iter_mut().find(|t| t.day == timing.day) {
+                Some(stored) => {
+                    stored.part_1_heap = timing.part_1_heap.or(stored.part_1_heap);
+                    stored.part_2_heap = timing.part_2_heap.or(stored.part_2_heap);
+                }
+                None => merged.data.push(Timing {
+                    part_1: None,
+                    part_2: None,
+                    total_nanos: 0_f64,
+                    ..timing.clone()
+                }),
+            }
+        }
+
+        merged.data.sort_unstable_by_key(|a| a.day);
+        merged
+    }
+
     /// Sum up total duration of timings as millis.
     pub fn total_millis(&self) -> f64 {
         self.data.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64
     }
 
+    /// Checks whether any timing carries heap statistics.
+    pub fn has_heap(&self) -> bool {
+        self.data
+            .iter()
+            .any(|t| t.part_1_heap.is_some() || t.part_2_heap.is_some())
+    }
+
     pub fn is_day_complete(&self, day: Day) -> bool {
         self.data
             .iter()
@@ -133,6 +183,26 @@ impl From<&Timing> for JsonValue {
             },
         );
 
+        for (key, heap) in [
+            ("part_1_heap", &value.part_1_heap),
+            ("part_2_heap", &value.part_2_heap),
+        ] {
+            if let Some(heap) = heap {
+                map.insert(key.into(), JsonValue::from(heap));
+            }
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        for (key, value) in [
+            ("median_nanos", value.median_nanos),
+            ("min_nanos", value.min_nanos),
+            ("samples", value.samples.map(|x| x as f64)),
+        ] {
+            if let Some(value) = value {
+                map.insert(key.into(), JsonValue::Number(value));
+            }
+        }
+
         JsonValue::Object(map)
     }
 }
@@ -166,11 +236,26 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // heap statistics are optional, timings stored without `--dhat` do not have them.
+        let get_heap = |key: &str| match json.get(key) {
+            Some(v) if !v.is_null() => HeapSummary::try_from(v).map(Some),
+            _ => Ok(None),
+        };
+
+        // bench statistics are optional as well, they were added after `total_nanos`.
+        let get_number = |key: &str| json.get(key).and_then(|v| v.get::<f64>().copied());
+
         Ok(Timing {
             day,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
             total_nanos,
+            part_1_heap: get_heap("part_1_heap")?,
+            part_2_heap: get_heap("part_2_heap")?,
+            median_nanos: get_number("median_nanos"),
+            min_nanos: get_number("min_nanos"),
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            samples: get_number("samples").map(|x| x as u64),
         })
     }
 }
@@ -191,18 +276,33 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: None,
                     total_nanos: 4e+10,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 },
             ],
         }
@@ -221,6 +321,19 @@ mod tests {
             assert_eq!(timing.part_1, Some("1ms".to_string()));
             assert_eq!(timing.part_2, None);
             assert_eq!(timing.total_nanos, 1_000_000_000_f64);
+            assert_eq!(timing.part_1_heap, None);
+        }
+
+        #[test]
+        fn handles_heap_summaries() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000, "part_1_heap": { "total_bytes": 2048, "peak_bytes": 1024, "allocations": 4 } }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            let heap = timing.part_1_heap.unwrap();
+            assert_eq!(heap.total_bytes, 2048);
+            assert_eq!(heap.peak_bytes, 1024);
+            assert_eq!(heap.allocations, 4);
+            assert_eq!(timing.part_2_heap, None);
         }
 
         #[test]
@@ -282,10 +395,15 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
                     total_nanos: 3_000_000_000_f64,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert_eq!(timings.is_day_complete(day!(1)), true);
         }
 
         #[test]
@@ -296,10 +414,15 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: None,
                     total_nanos: 1_000_000_000_f64,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
 
         #[test]
@@ -310,16 +433,22 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0.0,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
     }
 
     mod merge {
         use crate::{
             day,
+            template::memory::HeapSummary,
             template::timings::{Timing, Timings},
         };
 
@@ -334,6 +463,11 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -354,6 +488,11 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -365,6 +504,78 @@ mod tests {
             assert_eq!(merged.data[2].day, day!(4));
         }
 
+        #[test]
+        fn keeps_stored_heap_summaries() {
+            let mut timings = get_mock_timings();
+            timings.data[1].part_1_heap = Some(HeapSummary {
+                total_bytes: 2048,
+                peak_bytes: 1024,
+                allocations: 4,
+            });
+
+            let other = Timings {
+                data: vec![Timing {
+                    day: day!(2),
+                    part_1: Some("1ms".into()),
+                    part_2: None,
+                    total_nanos: 1e+6,
+                    part_1_heap: None,
+                    part_2_heap: None,
+                    median_nanos: None,
+                    min_nanos: None,
+                    samples: None,
+                }],
+            };
+            let merged = timings.merge(&other);
+
+            assert_eq!(merged.data[1].part_1, Some("1ms".into()));
+            assert_eq!(merged.data[1].part_1_heap.unwrap().peak_bytes, 1024);
+        }
+
+        #[test]
+        fn merges_heap_summaries() {
+            let heap = HeapSummary {
+                total_bytes: 2048,
+                peak_bytes: 1024,
+                allocations: 4,
+            };
+            let timings = get_mock_timings();
+            let other = Timings {
+                data: vec![
+                    Timing {
+                        day: day!(2),
+                        part_1: None,
+                        part_2: None,
+                        total_nanos: 0_f64,
+                        part_1_heap: Some(heap),
+                        part_2_heap: None,
+                        median_nanos: None,
+                        min_nanos: None,
+                        samples: None,
+                    },
+                    Timing {
+                        day: day!(3),
+                        part_1: Some("1ms".into()),
+                        part_2: None,
+                        total_nanos: 1e+6,
+                        part_1_heap: Some(heap),
+                        part_2_heap: None,
+                        median_nanos: None,
+                        min_nanos: None,
+                        samples: None,
+                    },
+                ],
+            };
+            let merged = timings.merge_heap(&other);
+
+            assert_eq!(merged.data.len(), 4);
+            assert_eq!(merged.data[1].part_1, Some("30ms".into()));
+            assert_eq!(merged.data[1].part_1_heap, Some(heap));
+            assert_eq!(merged.data[2].day, day!(3));
+            assert_eq!(merged.data[2].part_1, None);
+            assert_eq!(merged.data[2].part_1_heap, Some(heap));
+        }
+
         #[test]
         fn handles_empty_timings() {
             let timings = Timings::default();
@@ -0,0 +1,356 @@
+/// Visualisation of grid simulations. Solutions record frames with [`record`], which is a no-op
+/// unless the solution runs with `--visualize`. The recorded frames are then played back in the
+/// terminal or exported as text or as a PPM image sequence.
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, stdin, stdout, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::template::config::Config;
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static FRAMES: Mutex<Vec<Frame>> = Mutex::new(vec![]);
+
+/// Delay between frames when playing an animation.
+const FRAME_DELAY: Duration = Duration::from_millis(100);
+/// Width and height of a grid cell in exported images, in pixels.
+const CELL_SIZE: usize = 4;
+
+/// A single state of a grid simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Shown above the frame, e.g. the current step of the simulation.
+    pub caption: String,
+    rows: Vec<String>,
+}
+
+impl Frame {
+    /// Creates a frame from anything that renders a grid, one row per line.
+    pub fn new(caption: impl Into<String>, grid: impl Display) -> Self {
+        Self {
+            caption: caption.into(),
+            rows: grid.to_string().lines().map(String::from).collect(),
+        }
+    }
+
+    /// Creates a `width` x `height` frame from the character at each position.
+    pub fn from_fn(
+        caption: impl Into<String>,
+        width: usize,
+        height: usize,
+        cell: impl Fn(usize, usize) -> char,
+    ) -> Self {
+        Self {
+            caption: caption.into(),
+            rows: (0..height)
+                .map(|y| (0..width).map(|x| cell(x, y)).collect())
+                .collect(),
+        }
+    }
+
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Renders the frame as a binary PPM (`P6`) image, using [`CELL_SIZE`] pixels per cell.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let (width, height) = (self.width() * CELL_SIZE, self.height() * CELL_SIZE);
+        let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+        image.reserve(width * height * 3);
+
+        for row in &self.rows {
+            let mut line = Vec::with_capacity(width * 3);
+            let mut cells = row.chars();
+            for _ in 0..self.width() {
+                let color = cell_color(cells.next().unwrap_or(' '));
+                for _ in 0..CELL_SIZE {
+                    line.extend_from_slice(&color);
+                }
+            }
+            for _ in 0..CELL_SIZE {
+                image.extend_from_slice(&line);
+            }
+        }
+
+        image
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.rows.join("\n"))
+    }
+}
+
+/// Maps a grid cell to a color. Common puzzle characters get fixed colors, others a stable one
+/// derived from the character.
+fn cell_color(cell: char) -> [u8; 3] {
+    match cell {
+        '.' | ' ' => [20, 22, 30],
+        '#' => [190, 195, 210],
+        '^' | '>' | 'v' | '<' | '@' => [255, 200, 40],
+        'X' | 'O' | '*' => [80, 160, 255],
+        '0'..='9' => {
+            let shade = 80 + (cell as u8 - b'0') * 19;
+            [40, shade, 60]
+        }
+        _ => {
+            let hash = u32::from(cell).wrapping_mul(0x9E37_79B9).to_be_bytes();
+            [hash[0] | 0x40, hash[1] | 0x40, hash[2] | 0x40]
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Whether frames are currently being recorded. Use this to skip expensive bookkeeping that is
+/// only needed for the visualisation.
+pub fn is_recording() -> bool {
+    RECORDING.load(Ordering::Relaxed)
+}
+
+/// Records a frame. `frame` is only called when running with `--visualize`, so recording has no
+/// effect on regular and benchmarked runs.
+pub fn record(frame: impl FnOnce() -> Frame) {
+    if is_recording() {
+        let frame = frame();
+        FRAMES.lock().unwrap().push(frame);
+    }
+}
+
+/// Starts recording frames, dropping frames of a previous recording.
+pub(crate) fn start() {
+    FRAMES.lock().unwrap().clear();
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// Stops recording and returns the recorded frames.
+pub(crate) fn stop() -> Vec<Frame> {
+    RECORDING.store(false, Ordering::Relaxed);
+    std::mem::take(&mut *FRAMES.lock().unwrap())
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Where the frames of `--visualize` go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visualization {
+    /// Step through the frames in the terminal.
+    Terminal,
+    /// Write all frames to a text file.
+    Text,
+    /// Write every frame to a PPM image.
+    Ppm,
+}
+
+impl FromStr for Visualization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "terminal" => Ok(Self::Terminal),
+            "text" => Ok(Self::Text),
+            "ppm" => Ok(Self::Ppm),
+            _ => Err("expecting one of `terminal`, `text` or `ppm`".into()),
+        }
+    }
+}
+
+impl Display for Visualization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Terminal => "terminal",
+            Self::Text => "text",
+            Self::Ppm => "ppm",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Shows or exports the frames recorded for a part.
+pub fn present(frames: &[Frame], target: Visualization, day: Day, part: u8, config: &Config) {
+    if frames.is_empty() {
+        println!("Part {part} did not record any frames.");
+        return;
+    }
+
+    let result = match target {
+        Visualization::Terminal => play(frames, part),
+        Visualization::Text => export_text(frames, &output_path(config, day, part, "txt")),
+        Visualization::Ppm => export_ppm(frames, &output_path(config, day, part, "")),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Failed to visualize part {part}: {e}");
+    }
+}
+
+/// Path of an export in `data/visualizations`, e.g. `14-2.txt`, or `14-2/` without extension.
+fn output_path(config: &Config, day: Day, part: u8, extension: &str) -> PathBuf {
+    let name = if extension.is_empty() {
+        format!("{day}-{part}")
+    } else {
+        format!("{day}-{part}.{extension}")
+    };
+    config.data_path("visualizations").join(name)
+}
+
+fn frame_heading(frames: &[Frame], idx: usize) -> String {
+    let frame = &frames[idx];
+    let mut heading = format!("frame {}/{}", idx + 1, frames.len());
+    if !frame.caption.is_empty() {
+        heading.push_str(&format!(": {}", frame.caption));
+    }
+    heading
+}
+
+fn export_text(frames: &[Frame], path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = (0..frames.len())
+        .map(|idx| format!("--- {} ---\n{}\n", frame_heading(frames, idx), frames[idx]))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    fs::write(path, content)?;
+    println!("Wrote {} frames to {}.", frames.len(), path.display());
+    Ok(())
+}
+
+fn export_ppm(frames: &[Frame], dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    for (idx, frame) in frames.iter().enumerate() {
+        fs::write(
+            dir.join(format!("frame-{:05}.ppm", idx + 1)),
+            frame.to_ppm(),
+        )?;
+    }
+
+    println!(
+        "Wrote {} frames to {}/frame-*.ppm.",
+        frames.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Steps through the frames in the terminal. Without an interactive terminal, all frames are
+/// printed one after another.
+fn play(frames: &[Frame], part: u8) -> io::Result<()> {
+    let mut stdout = stdout();
+
+    if !stdin().is_terminal() || !stdout.is_terminal() {
+        for idx in 0..frames.len() {
+            writeln!(
+                stdout,
+                "{ANSI_BOLD}Part {part}{ANSI_RESET} {}\n{}\n",
+                frame_heading(frames, idx),
+                frames[idx]
+            )?;
+        }
+        return Ok(());
+    }
+
+    let mut idx = 0;
+    let mut lines = stdin().lock().lines();
+
+    loop {
+        // clear the screen and move the cursor to the top left.
+        write!(stdout, "\x1b[2J\x1b[H")?;
+        writeln!(
+            stdout,
+            "{ANSI_BOLD}Part {part}{ANSI_RESET} {}\n{}\n",
+            frame_heading(frames, idx),
+            frames[idx]
+        )?;
+        write!(
+            stdout,
+            "{ANSI_ITALIC}[enter] next, [b] back, [p] play, [<n>] jump to frame, [q] quit{ANSI_RESET} "
+        )?;
+        stdout.flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            return Ok(());
+        };
+
+        match line.trim() {
+            "" | "n" => idx = (idx + 1).min(frames.len() - 1),
+            "b" => idx = idx.saturating_sub(1),
+            "q" => return Ok(()),
+            "p" => {
+                while idx + 1 < frames.len() {
+                    idx += 1;
+                    write!(stdout, "\x1b[2J\x1b[H")?;
+                    writeln!(
+                        stdout,
+                        "{ANSI_BOLD}Part {part}{ANSI_RESET} {}\n{}",
+                        frame_heading(frames, idx),
+                        frames[idx]
+                    )?;
+                    stdout.flush()?;
+                    thread::sleep(FRAME_DELAY);
+                }
+            }
+            n => {
+                if let Ok(frame) = n.parse::<usize>() {
+                    idx = frame.clamp(1, frames.len()) - 1;
+                }
+            }
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{cell_color, Frame, Visualization};
+
+    #[test]
+    fn builds_frames() {
+        let frame = Frame::from_fn("step 1", 3, 2, |x, y| if x == y { '#' } else { '.' });
+        assert_eq!(frame.to_string(), "#..\n.#.");
+        assert_eq!((frame.width(), frame.height()), (3, 2));
+        assert_eq!(Frame::new("", "#.\n.#\n"), Frame::new("", "#.\n.#"));
+    }
+
+    #[test]
+    fn renders_ppm() {
+        let frame = Frame::new("", "#.\n.");
+        let ppm = frame.to_ppm();
+        let header = b"P6\n8 8\n255\n";
+        assert!(ppm.starts_with(header));
+        assert_eq!(ppm.len(), header.len() + 8 * 8 * 3);
+        // the top left pixel is a wall, the bottom right one is padding.
+        assert_eq!(ppm[header.len()..header.len() + 3], cell_color('#'));
+        assert_eq!(ppm[ppm.len() - 3..], cell_color(' '));
+    }
+
+    #[test]
+    fn parses_visualizations() {
+        assert_eq!("ppm".parse(), Ok(Visualization::Ppm));
+        assert_eq!(Visualization::Terminal.to_string(), "terminal");
+        assert!("gif".parse::<Visualization>().is_err());
+    }
+}
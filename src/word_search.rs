@@ -0,0 +1,322 @@
+//! Word search in a grid of letters: words in any of the eight directions and 2D patterns with
+//! wildcards, e.g. the X-MAS cross.
+
+/// Offsets of the eight directions a word can be read in, clockwise from north.
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Matches any letter in a [`Pattern`].
+pub const WILDCARD: u8 = b'.';
+
+/// A match of a word or pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Position `(x, y)` of the first letter of a word, or of the top left corner of a pattern.
+    pub start: (usize, usize),
+    /// Positions of the matched letters. Wildcards of a pattern are not included.
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// A rectangular template of letters and [`WILDCARD`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    width: usize,
+    height: usize,
+    /// Offsets `(x, y)` and letters of the non-wildcard cells.
+    letters: Vec<(usize, usize, u8)>,
+}
+
+impl Pattern {
+    /// Builds a pattern from its rows, e.g. `&["M.S", ".A.", "M.S"]`. Shorter rows are padded
+    /// with wildcards.
+    pub fn new(rows: &[&str]) -> Self {
+        let letters = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.bytes()
+                    .enumerate()
+                    .filter(|(_, letter)| *letter != WILDCARD)
+                    .map(move |(x, letter)| (x, y, letter.to_ascii_uppercase()))
+            })
+            .collect();
+
+        Pattern {
+            width: rows.iter().map(|row| row.len()).max().unwrap_or(0),
+            height: rows.len(),
+            letters,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Rotates the pattern by 90° clockwise.
+    pub fn rotate(&self) -> Self {
+        let mut letters: Vec<_> = self
+            .letters
+            .iter()
+            .map(|&(x, y, letter)| (self.height - 1 - y, x, letter))
+            .collect();
+        letters.sort_unstable_by_key(|&(x, y, _)| (y, x));
+
+        Pattern {
+            width: self.height,
+            height: self.width,
+            letters,
+        }
+    }
+
+    /// The distinct rotations of the pattern, starting with the pattern itself. Symmetric
+    /// patterns have fewer than four, so that a location is not counted twice.
+    pub fn rotations(&self) -> Vec<Self> {
+        let mut rotations = vec![self.clone()];
+        for _ in 0..3 {
+            let next = rotations.last().unwrap().rotate();
+            if !rotations.contains(&next) {
+                rotations.push(next);
+            }
+        }
+        rotations
+    }
+}
+
+/// A grid of letters. Letters are compared case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    width: usize,
+    height: usize,
+    letters: Vec<u8>,
+}
+
+impl WordSearch {
+    /// Reads the grid from the lines of `input`. Lines are expected to have the same length.
+    pub fn new(input: &str) -> Self {
+        let rows: Vec<&str> = input.lines().filter(|line| !line.is_empty()).collect();
+        WordSearch {
+            width: rows.first().map_or(0, |row| row.len()),
+            height: rows.len(),
+            letters: rows
+                .iter()
+                .flat_map(|row| row.bytes().map(|letter| letter.to_ascii_uppercase()))
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<u8> {
+        (x < self.width && y < self.height).then(|| self.letters[y * self.width + x])
+    }
+
+    /// Positions of the letters of `word` when read from `start` in `direction`, or `None` if
+    /// the word does not fit in the grid or does not match.
+    fn word_at(
+        &self,
+        word: &[u8],
+        start: (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<impl Iterator<Item = (usize, usize)>> {
+        // the grid is checked at the last letter only, the letters in between fit if both ends do.
+        let steps = word.len() as isize - 1;
+        let end = (
+            start.0.checked_add_signed(dx * steps)?,
+            start.1.checked_add_signed(dy * steps)?,
+        );
+        self.get(end)?;
+
+        let cells = (0..word.len() as isize).map(move |step| {
+            (
+                start.0.wrapping_add_signed(dx * step),
+                start.1.wrapping_add_signed(dy * step),
+            )
+        });
+
+        cells
+            .clone()
+            .zip(word)
+            .all(|((x, y), letter)| self.letters[y * self.width + x] == *letter)
+            .then_some(cells)
+    }
+
+    fn starts(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+    }
+
+    fn normalize(word: &str) -> Vec<u8> {
+        word.bytes()
+            .map(|letter| letter.to_ascii_uppercase())
+            .collect()
+    }
+
+    /// Counts the occurrences of `word` in all eight directions. Palindromes are counted once
+    /// per direction they can be read in.
+    pub fn count_word(&self, word: &str) -> usize {
+        let word = Self::normalize(word);
+        if word.is_empty() {
+            return 0;
+        }
+
+        self.starts()
+            .filter(|&start| self.get(start) == Some(word[0]))
+            .map(|start| {
+                DIRECTIONS
+                    .iter()
+                    .filter(|&&direction| self.word_at(&word, start, direction).is_some())
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Finds the occurrences of `word` in all eight directions, in reading order of their first
+    /// letter and clockwise from north.
+    pub fn find_word(&self, word: &str) -> Vec<Match> {
+        let word = Self::normalize(word);
+        if word.is_empty() {
+            return vec![];
+        }
+        let word = &word;
+
+        self.starts()
+            .filter(|&start| self.get(start) == Some(word[0]))
+            .flat_map(|start| {
+                DIRECTIONS.iter().filter_map(move |&direction| {
+                    let cells = self.word_at(word, start, direction)?;
+                    Some(Match {
+                        start,
+                        cells: cells.collect(),
+                    })
+                })
+            })
+            .collect()
+    }
+
+    fn pattern_at(&self, pattern: &Pattern, (x, y): (usize, usize)) -> bool {
+        pattern
+            .letters
+            .iter()
+            .all(|&(dx, dy, letter)| self.letters[(y + dy) * self.width + x + dx] == letter)
+    }
+
+    /// Top left corners at which `pattern` matches, in reading order.
+    fn pattern_starts<'a>(
+        &'a self,
+        pattern: &'a Pattern,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let fits_x = (self.width + 1).saturating_sub(pattern.width);
+        let fits_y = (self.height + 1).saturating_sub(pattern.height);
+        (0..fits_y)
+            .flat_map(move |y| (0..fits_x).map(move |x| (x, y)))
+            .filter(|&start| self.pattern_at(pattern, start))
+    }
+
+    /// Counts the locations at which `pattern` matches, without rotating it.
+    pub fn count_pattern(&self, pattern: &Pattern) -> usize {
+        self.pattern_starts(pattern).count()
+    }
+
+    /// Finds the locations at which `pattern` matches, without rotating it, in reading order.
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<Match> {
+        self.pattern_starts(pattern)
+            .map(|(x, y)| Match {
+                start: (x, y),
+                cells: pattern
+                    .letters
+                    .iter()
+                    .map(|&(dx, dy, _)| (x + dx, y + dy))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Renders the grid with the letters that are not part of any match replaced by `.`.
+    pub fn highlight(&self, matches: &[Match]) -> String {
+        let mut matched = vec![false; self.letters.len()];
+        for (x, y) in matches.iter().flat_map(|m| &m.cells) {
+            matched[y * self.width + x] = true;
+        }
+
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for (idx, letter) in self.letters.iter().enumerate() {
+            out.push(if matched[idx] { *letter as char } else { '.' });
+            if idx % self.width == self.width - 1 {
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Pattern, WordSearch};
+
+    const GRID: &str = "..X...\n.SAMX.\n.A..A.\nXMAS.S\n.X....\n";
+
+    #[test]
+    fn finds_words() {
+        let search = WordSearch::new(GRID);
+        assert_eq!(search.count_word("XMAS"), 4);
+        assert_eq!(search.count_word("xmas"), 4);
+        assert_eq!(search.count_word(""), 0);
+
+        let matches = search.find_word("XMAS");
+        assert_eq!(matches.len(), 4);
+        assert_eq!(matches[0].start, (2, 0));
+        assert_eq!(matches[0].cells, [(2, 0), (3, 1), (4, 2), (5, 3)]);
+    }
+
+    #[test]
+    fn highlights_matches() {
+        let search = WordSearch::new("ABC\nDEF\n");
+        let highlighted = search.highlight(&search.find_word("be"));
+        assert_eq!(highlighted, ".B.\n.E.\n");
+    }
+
+    #[test]
+    fn rotates_patterns() {
+        let cross = Pattern::new(&["M.S", ".A.", "M.S"]);
+        let rotations = cross.rotations();
+        assert_eq!(rotations.len(), 4);
+        assert_eq!(rotations[1], Pattern::new(&["M.M", ".A.", "S.S"]));
+        assert_eq!(rotations[0], rotations[3].rotate());
+
+        assert_eq!(Pattern::new(&["A.A", ".A.", "A.A"]).rotations().len(), 1);
+        let bar = Pattern::new(&["AB"]).rotate();
+        assert_eq!((bar.width(), bar.height()), (1, 2));
+    }
+
+    #[test]
+    fn finds_patterns() {
+        let search = WordSearch::new("MXS\nXAX\nMXS\n");
+        let cross = Pattern::new(&["M.S", ".A.", "M.S"]);
+        assert_eq!(search.count_pattern(&cross), 1);
+        assert_eq!(search.count_pattern(&cross.rotate()), 0);
+
+        let matches = search.find_pattern(&cross);
+        assert_eq!(matches[0].start, (0, 0));
+        assert_eq!(matches[0].cells, [(0, 0), (2, 0), (1, 1), (0, 2), (2, 2)]);
+        assert_eq!(search.count_pattern(&Pattern::new(&["MXSM"])), 0);
+    }
+}
@@ -0,0 +1,32 @@
+use std::fs;
+use std::process::Command;
+
+use tinyjson::JsonValue;
+
+/// Every line a benched solution prints in JSON mode has to be a JSON object on its own.
+#[test]
+fn time_prints_json_lines() {
+    let data_dir = std::env::temp_dir().join(format!("aoc-json-{}", std::process::id()));
+    fs::create_dir_all(data_dir.join("inputs")).unwrap();
+    fs::copy("data/examples/01.txt", data_dir.join("inputs/01.txt")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_01"))
+        .args(["--time", "--format", "json", "--bench-budget", "1"])
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&data_dir).unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    for (part, line) in lines.iter().enumerate() {
+        let json: JsonValue = line.parse().unwrap();
+        let object: &std::collections::HashMap<_, _> = json.get().unwrap();
+        assert_eq!(object["part"], JsonValue::Number(part as f64 + 1.0));
+        assert!(matches!(object["samples"], JsonValue::Number(samples) if samples > 1.0));
+    }
+}